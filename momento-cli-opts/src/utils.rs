@@ -1,4 +1,4 @@
-use std::num::IntErrorKind;
+use std::{num::IntErrorKind, time::Duration};
 
 use chrono::NaiveDate;
 
@@ -8,6 +8,28 @@ pub struct Bounds {
     pub max: u32,
 }
 
+impl Bounds {
+    /// The rule `parse_bounds` applies, for bounds that didn't come from a flag.
+    pub fn validate(self) -> Result<Self, String> {
+        if self.min > self.max {
+            return Err(format!(
+                "bounds are inverted: {}..{} (MIN must not exceed MAX)",
+                self.min, self.max
+            ));
+        }
+        Ok(self)
+    }
+
+    /// The rules `parse_positive_bounds` applies, for bounds that didn't come from a flag.
+    pub fn validate_positive(self) -> Result<Self, String> {
+        let bounds = self.validate()?;
+        if bounds.min == 0 {
+            return Err("bounds must be >0".to_string());
+        }
+        Ok(bounds)
+    }
+}
+
 pub fn parse_bounds(s: &str) -> Result<Bounds, String> {
     let (min, max) = match s.split_once("..") {
         None => (s, s),
@@ -19,25 +41,31 @@ pub fn parse_bounds(s: &str) -> Result<Bounds, String> {
             &_ => format!("'{bound}' is not a whole number; expected whole number `N` (pinned) or whole numbers `MIN..MAX`"),
         })
     };
-    let bounds = Bounds {
+    Bounds {
         min: parse(min)?,
         max: parse(max)?,
-    };
-    if bounds.min > bounds.max {
-        return Err(format!(
-            "bounds are inverted: {}..{} (MIN must not exceed MAX)",
-            bounds.min, bounds.max
-        ));
     }
-    Ok(bounds)
+    .validate()
 }
 
 pub fn parse_positive_bounds(s: &str) -> Result<Bounds, String> {
-    let bounds = parse_bounds(s)?;
-    if bounds.min == 0 {
-        return Err("bounds must be >0".to_string());
+    parse_bounds(s)?.validate_positive()
+}
+
+/// The rule behind `--shard-count`, for shard counts that didn't come from a flag.
+pub fn validate_shard_count(shard_count: u32) -> Result<u32, String> {
+    if shard_count == 0 {
+        return Err("shard count must be >0".to_string());
     }
-    Ok(bounds)
+    Ok(shard_count)
+}
+
+pub fn parse_shard_count(s: &str) -> Result<u32, String> {
+    let shard_count = s
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("'{s}' is not a whole number"))?;
+    validate_shard_count(shard_count)
 }
 
 #[derive(Debug, Clone, clap::ValueEnum)]
@@ -46,7 +74,258 @@ pub enum CapacityPoolProvisioningMode {
     Flex,
 }
 
+/// An availability zone as given to `--zones`: the ID the API takes, like `usw2-az1`, or a
+/// name like `us-west-2a`, which only means something within one AWS account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AvailabilityZone {
+    Id(String),
+    Name(String),
+}
+
+impl std::fmt::Display for AvailabilityZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Id(zone) | Self::Name(zone) => write!(f, "{zone}"),
+        }
+    }
+}
+
+/// Parses a zone ID (`<region-code>-az<N>`, e.g. `usw2-az1`) or a zone name (e.g. `us-west-2a`).
+pub fn parse_availability_zone(s: &str) -> Result<AvailabilityZone, String> {
+    let s = s.trim();
+    if is_zone_id(s) {
+        Ok(AvailabilityZone::Id(s.to_string()))
+    } else if is_zone_name(s) {
+        Ok(AvailabilityZone::Name(s.to_string()))
+    } else {
+        Err(format!(
+            "'{s}' is not an availability zone ID; expected <region-code>-az<N>, e.g. usw2-az1"
+        ))
+    }
+}
+
+fn is_zone_id(s: &str) -> bool {
+    let Some((region_code, number)) = s.split_once("-az") else {
+        return false;
+    };
+    let digits = region_code.trim_start_matches(|c: char| c.is_ascii_lowercase());
+    digits.len() < region_code.len()
+        && !digits.is_empty()
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_zone_name(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    let Some((last, rest)) = parts.split_last() else {
+        return false;
+    };
+    let number = last.trim_end_matches(|c: char| c.is_ascii_lowercase());
+    rest.len() >= 2
+        && rest.iter().all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+        && last.len() == number.len() + 1
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
 pub fn parse_date(s: &str) -> Result<NaiveDate, String> {
     NaiveDate::parse_from_str(s, "%Y-%m-%d")
         .map_err(|_| "Date must be in YYYY-MM-DD format".to_string())
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// How `database credentials` prints a database's connection settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CredentialsFormat {
+    Env,
+    Json,
+    RedisUrl,
+}
+
+/// Which password `database credentials` prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CredentialsToken {
+    /// The profile's API key.
+    ApiKey,
+    /// A disposable token, vended now, that can only reach this database.
+    Disposable,
+}
+
+/// Parses a duration such as `500ms`, `60s`, `5m`, or `1h`; a bare number is seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(split);
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|_| format!("'{s}' is not a duration; expected e.g. 500ms, 60s, 5m or 1h"))?;
+    let too_large = || format!("'{s}' is too large");
+    let duration = match unit.trim() {
+        "ms" => Duration::from_millis(amount),
+        "" | "s" => Duration::from_secs(amount),
+        "m" => Duration::from_secs(amount.checked_mul(60).ok_or_else(too_large)?),
+        "h" => Duration::from_secs(amount.checked_mul(60 * 60).ok_or_else(too_large)?),
+        unit => {
+            return Err(format!(
+                "unknown unit '{unit}' in '{s}'; expected ms, s, m or h"
+            ))
+        }
+    };
+    if duration.is_zero() {
+        return Err("duration must be >0".to_string());
+    }
+    Ok(duration)
+}
+
+/// Parses a size such as `512`, `1KiB`, `4MiB`, or `1KB`; a bare number is bytes.
+pub fn parse_byte_size(s: &str) -> Result<usize, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(split);
+    let amount = amount
+        .parse::<usize>()
+        .map_err(|_| format!("'{s}' is not a size; expected e.g. 512, 1KiB or 4MiB"))?;
+    let multiplier = match unit.trim() {
+        "" | "B" => 1,
+        "KB" => 1_000,
+        "KiB" => 1 << 10,
+        "MB" => 1_000_000,
+        "MiB" => 1 << 20,
+        unit => {
+            return Err(format!(
+                "unknown unit '{unit}' in '{s}'; expected B, KB, KiB, MB or MiB"
+            ))
+        }
+    };
+    amount
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("'{s}' is too large"))
+}
+
+/// The relative share of gets and sets in a benchmark's workload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GetSetRatio {
+    pub gets: u32,
+    pub sets: u32,
+}
+
+/// Parses `get:set=9:1`, or just `9:1`.
+pub fn parse_get_set_ratio(s: &str) -> Result<GetSetRatio, String> {
+    let ratio = s.trim().strip_prefix("get:set=").unwrap_or(s.trim());
+    let invalid = || format!("'{s}' is not a ratio; expected get:set=GETS:SETS, e.g. get:set=9:1");
+    let (gets, sets) = ratio.split_once(':').ok_or_else(invalid)?;
+    let ratio = GetSetRatio {
+        gets: gets.trim().parse().map_err(|_| invalid())?,
+        sets: sets.trim().parse().map_err(|_| invalid())?,
+    };
+    if ratio.gets == 0 && ratio.sets == 0 {
+        return Err("at least one of gets and sets must be >0".to_string());
+    }
+    Ok(ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("60s"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("10d").is_err());
+        assert!(parse_duration("s").is_err());
+        assert_eq!(
+            parse_duration("18446744073709551615m"),
+            Err("'18446744073709551615m' is too large".to_string())
+        );
+        assert_eq!(
+            parse_duration("5124095576030432h"),
+            Err("'5124095576030432h' is too large".to_string())
+        );
+    }
+
+    #[test]
+    fn byte_sizes() {
+        assert_eq!(parse_byte_size("512"), Ok(512));
+        assert_eq!(parse_byte_size("1KiB"), Ok(1024));
+        assert_eq!(parse_byte_size("1KB"), Ok(1000));
+        assert_eq!(parse_byte_size("4MiB"), Ok(4 * 1024 * 1024));
+        assert!(parse_byte_size("1GiB").is_err());
+        assert!(parse_byte_size("KiB").is_err());
+    }
+
+    #[test]
+    fn shard_counts() {
+        assert_eq!(parse_shard_count("3"), Ok(3));
+        assert_eq!(
+            parse_shard_count("0"),
+            Err("shard count must be >0".to_string())
+        );
+        assert!(parse_shard_count("-1").is_err());
+        assert_eq!(validate_shard_count(0), parse_shard_count("0"));
+    }
+
+    #[test]
+    fn get_set_ratios() {
+        assert_eq!(
+            parse_get_set_ratio("get:set=9:1"),
+            Ok(GetSetRatio { gets: 9, sets: 1 })
+        );
+        assert_eq!(
+            parse_get_set_ratio("0:1"),
+            Ok(GetSetRatio { gets: 0, sets: 1 })
+        );
+        assert!(parse_get_set_ratio("0:0").is_err());
+        assert!(parse_get_set_ratio("9").is_err());
+        assert!(parse_get_set_ratio("get:set=a:b").is_err());
+    }
+
+    #[test]
+    fn availability_zones() {
+        assert_eq!(
+            parse_availability_zone("usw2-az1"),
+            Ok(AvailabilityZone::Id("usw2-az1".to_string()))
+        );
+        assert_eq!(
+            parse_availability_zone("apse1-az12"),
+            Ok(AvailabilityZone::Id("apse1-az12".to_string()))
+        );
+        assert_eq!(
+            parse_availability_zone("us-west-2a"),
+            Ok(AvailabilityZone::Name("us-west-2a".to_string()))
+        );
+        assert_eq!(
+            parse_availability_zone("us-gov-west-1b"),
+            Ok(AvailabilityZone::Name("us-gov-west-1b".to_string()))
+        );
+        for invalid in [
+            "usw2",
+            "usw2-az",
+            "az1",
+            "2-az1",
+            "us-west-2",
+            "usw2-azone",
+            "USW2-AZ1",
+        ] {
+            assert!(
+                parse_availability_zone(invalid).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::{collections::BTreeMap, num::NonZeroU32, sync::Arc, time::Duration};
+
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use momento::{cache::GetResponse, CacheClient};
+use momento_cli_opts::{GetSetRatio, OutputFormat};
+use rand::Rng;
+use serde::Serialize;
+use tokio::{task::JoinSet, time::Instant};
+
+use crate::{
+    error::CliError,
+    utils::{
+        console::{console_data, console_info},
+        dry_run::skip_sdk_request,
+        latency::LatencyPercentiles,
+    },
+};
+
+pub struct BenchOptions {
+    pub cache_name: String,
+    pub workers: u32,
+    pub duration: Duration,
+    pub ratio: GetSetRatio,
+    pub value_size: usize,
+    pub keyspace: u64,
+    pub rate: Option<u32>,
+    pub output: OutputFormat,
+}
+
+/// What one worker saw. Latencies are in microseconds, and only for requests that succeeded.
+#[derive(Debug, Default)]
+struct WorkerStats {
+    get_latencies: Vec<u64>,
+    set_latencies: Vec<u64>,
+    hits: u64,
+    misses: u64,
+    errors: BTreeMap<String, u64>,
+}
+
+impl WorkerStats {
+    fn merge(&mut self, other: WorkerStats) {
+        self.get_latencies.extend(other.get_latencies);
+        self.set_latencies.extend(other.set_latencies);
+        self.hits += other.hits;
+        self.misses += other.misses;
+        for (code, count) in other.errors {
+            *self.errors.entry(code).or_default() += count;
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct OperationReport {
+    pub count: u64,
+    pub latency_ms: Option<LatencyPercentiles>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BenchReport {
+    pub cache_name: String,
+    pub workers: u32,
+    pub elapsed_seconds: f64,
+    pub operations: u64,
+    pub throughput_per_second: f64,
+    pub get: OperationReport,
+    pub set: OperationReport,
+    pub hits: u64,
+    pub misses: u64,
+    /// Failed requests, by `MomentoErrorCode`.
+    pub errors: BTreeMap<String, u64>,
+}
+
+pub async fn bench(client: CacheClient, options: BenchOptions) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "Bench",
+        &[
+            ("cache_name", options.cache_name.clone()),
+            ("workers", options.workers.to_string()),
+            ("duration", format!("{:?}", options.duration)),
+            (
+                "ratio",
+                format!("get:set={}:{}", options.ratio.gets, options.ratio.sets),
+            ),
+            ("value_size", options.value_size.to_string()),
+            ("keyspace", options.keyspace.to_string()),
+            (
+                "rate",
+                options
+                    .rate
+                    .map_or("unlimited".to_string(), |rate| rate.to_string()),
+            ),
+        ],
+    ) {
+        return Ok(());
+    }
+
+    let limiter = options
+        .rate
+        .and_then(NonZeroU32::new)
+        .map(|rate| Arc::new(RateLimiter::direct(Quota::per_second(rate))));
+    let cache_name = Arc::new(options.cache_name.clone());
+    let value = Arc::new(vec![b'x'; options.value_size]);
+
+    console_info!(
+        "Benchmarking cache {} with {} workers for {:?}...",
+        options.cache_name,
+        options.workers,
+        options.duration
+    );
+    let started = Instant::now();
+    let deadline = started + options.duration;
+    let mut workers = JoinSet::new();
+    for _ in 0..options.workers {
+        workers.spawn(run_worker(
+            client.clone(),
+            cache_name.clone(),
+            value.clone(),
+            options.ratio,
+            options.keyspace,
+            limiter.clone(),
+            deadline,
+        ));
+    }
+    let mut stats = WorkerStats::default();
+    while let Some(worker_stats) = workers.join_next().await {
+        let worker_stats =
+            worker_stats.map_err(|e| CliError::new(format!("A benchmark worker failed: {e}")))?;
+        stats.merge(worker_stats);
+    }
+
+    let report = summarize(&options, started.elapsed(), stats);
+    match options.output {
+        OutputFormat::Text => console_data!("{}", format_report(&report)),
+        OutputFormat::Json => console_data!(
+            "{}",
+            serde_json::to_string_pretty(&report)
+                .map_err(|e| CliError::new(format!("Could not serialize the report: {e}")))?
+        ),
+    }
+    Ok(())
+}
+
+async fn run_worker(
+    client: CacheClient,
+    cache_name: Arc<String>,
+    value: Arc<Vec<u8>>,
+    ratio: GetSetRatio,
+    keyspace: u64,
+    limiter: Option<Arc<DefaultDirectRateLimiter>>,
+    deadline: Instant,
+) -> WorkerStats {
+    let mut stats = WorkerStats::default();
+    while Instant::now() < deadline {
+        if let Some(limiter) = &limiter {
+            limiter.until_ready().await;
+        }
+        // Rng isn't Send, so it can't be held across the request.
+        let (is_get, key) = {
+            let mut rng = rand::thread_rng();
+            (
+                rng.gen_range(0..ratio.gets + ratio.sets) < ratio.gets,
+                format!("bench-{}", rng.gen_range(0..keyspace)),
+            )
+        };
+
+        let request_started = Instant::now();
+        let result = if is_get {
+            client
+                .get(cache_name.as_str(), key)
+                .await
+                .map(|response| Some(matches!(response, GetResponse::Hit { .. })))
+        } else {
+            client
+                .set(cache_name.as_str(), key, value.to_vec())
+                .await
+                .map(|_| None)
+        };
+        let latency = request_started.elapsed().as_micros() as u64;
+
+        match result {
+            Ok(hit) => {
+                if is_get {
+                    stats.get_latencies.push(latency);
+                } else {
+                    stats.set_latencies.push(latency);
+                }
+                match hit {
+                    Some(true) => stats.hits += 1,
+                    Some(false) => stats.misses += 1,
+                    None => {}
+                }
+            }
+            Err(e) => {
+                *stats
+                    .errors
+                    .entry(format!("{:?}", e.error_code))
+                    .or_default() += 1;
+            }
+        }
+    }
+    stats
+}
+
+fn operation_report(latencies: Vec<u64>) -> OperationReport {
+    OperationReport {
+        count: latencies.len() as u64,
+        latency_ms: LatencyPercentiles::from_micros(latencies),
+    }
+}
+
+fn summarize(options: &BenchOptions, elapsed: Duration, stats: WorkerStats) -> BenchReport {
+    let error_count: u64 = stats.errors.values().sum();
+    let get = operation_report(stats.get_latencies);
+    let set = operation_report(stats.set_latencies);
+    let operations = get.count + set.count + error_count;
+    BenchReport {
+        cache_name: options.cache_name.clone(),
+        workers: options.workers,
+        elapsed_seconds: elapsed.as_secs_f64(),
+        operations,
+        throughput_per_second: operations as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        get,
+        set,
+        hits: stats.hits,
+        misses: stats.misses,
+        errors: stats.errors,
+    }
+}
+
+fn format_report(report: &BenchReport) -> String {
+    let mut text = format!(
+        "Cache {}: {} requests from {} workers in {:.1}s ({:.1} requests/s)\n\n{:<4}  {:>10}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}",
+        report.cache_name,
+        report.operations,
+        report.workers,
+        report.elapsed_seconds,
+        report.throughput_per_second,
+        "op",
+        "succeeded",
+        "p50 ms",
+        "p90 ms",
+        "p99 ms",
+        "p99.9 ms",
+        "max ms",
+    );
+    for (name, operation) in [("get", &report.get), ("set", &report.set)] {
+        text.push_str(&format!("\n{name:<4}  {:>10}", operation.count));
+        if let Some(latency) = &operation.latency_ms {
+            text.push_str(&format!(
+                "  {:>9.3}  {:>9.3}  {:>9.3}  {:>9.3}  {:>9.3}",
+                latency.p50, latency.p90, latency.p99, latency.p999, latency.max
+            ));
+        }
+    }
+    if report.get.count > 0 {
+        text.push_str(&format!(
+            "\n\nGets: {} hits, {} misses",
+            report.hits, report.misses
+        ));
+    }
+    if report.errors.is_empty() {
+        text.push_str("\n\nErrors: none");
+    } else {
+        text.push_str("\n\nErrors:");
+        for (code, count) in &report.errors {
+            text.push_str(&format!("\n  {code}: {count}"));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> BenchOptions {
+        BenchOptions {
+            cache_name: "bench-cache".to_string(),
+            workers: 4,
+            duration: Duration::from_secs(10),
+            ratio: GetSetRatio { gets: 9, sets: 1 },
+            value_size: 1024,
+            keyspace: 100,
+            rate: None,
+            output: OutputFormat::Text,
+        }
+    }
+
+    #[test]
+    fn worker_stats_merge() {
+        let mut stats = WorkerStats {
+            get_latencies: vec![1000],
+            hits: 1,
+            errors: BTreeMap::from([("TimeoutError".to_string(), 1)]),
+            ..Default::default()
+        };
+        stats.merge(WorkerStats {
+            get_latencies: vec![2000],
+            set_latencies: vec![3000],
+            misses: 1,
+            errors: BTreeMap::from([("TimeoutError".to_string(), 2)]),
+            ..Default::default()
+        });
+        assert_eq!(stats.get_latencies, vec![1000, 2000]);
+        assert_eq!(stats.set_latencies, vec![3000]);
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert_eq!(stats.errors["TimeoutError"], 3);
+    }
+
+    #[test]
+    fn report_counts_errors_as_requests() {
+        let stats = WorkerStats {
+            get_latencies: vec![3000, 1000, 2000],
+            set_latencies: vec![],
+            hits: 2,
+            misses: 1,
+            errors: BTreeMap::from([("LimitExceededError".to_string(), 1)]),
+        };
+        let report = summarize(&options(), Duration::from_secs(2), stats);
+        assert_eq!(report.operations, 4);
+        assert_eq!(report.throughput_per_second, 2.0);
+        assert_eq!(report.get.latency_ms.as_ref().map(|l| l.p50), Some(2.0));
+        assert_eq!(report.set.latency_ms, None);
+
+        assert_eq!(
+            format_report(&report),
+            "Cache bench-cache: 4 requests from 4 workers in 2.0s (2.0 requests/s)
+
+op     succeeded     p50 ms     p90 ms     p99 ms   p99.9 ms     max ms
+get            3      2.000      3.000      3.000      3.000      3.000
+set            0
+
+Gets: 2 hits, 1 misses
+
+Errors:
+  LimitExceededError: 1"
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use futures::{stream, StreamExt};
+use momento::cache::{CacheClient, CreateCacheResponse};
+use regex::Regex;
+
+use crate::{
+    error::CliError,
+    utils::{
+        client::interact_with_momento,
+        confirm::{ResourceKind, Safeguards},
+        console::{console_data, console_info},
+        dry_run::skip_sdk_request,
+    },
+};
+
+/// How many creates or deletes to have in flight at once.
+const CONCURRENCY: usize = 8;
+
+/// Compiles a shell-style pattern, where `*` matches any run of characters and `?` any one.
+fn glob_to_regex(pattern: &str) -> Result<Regex, CliError> {
+    let mut regex = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| CliError::new(format!("Invalid pattern {pattern:?}: {e}")))
+}
+
+/// Reads cache names one per line, ignoring blank lines and `#` comments.
+fn parse_names(contents: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        let name = line.split('#').next().unwrap_or_default().trim();
+        if !name.is_empty() && !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Splits the caches matching `pattern` into those to delete and those that are protected.
+fn plan_deletes(
+    cache_names: Vec<String>,
+    pattern: &Regex,
+    safeguards: &Safeguards,
+) -> (Vec<String>, Vec<String>) {
+    cache_names
+        .into_iter()
+        .filter(|name| pattern.is_match(name))
+        .partition(|name| safeguards.force || !safeguards.protected.contains(name))
+}
+
+fn format_plan(action: &str, names: &[String]) -> String {
+    let mut plan = format!("Will {action} {} cache(s):", names.len());
+    for name in names {
+        plan.push_str(&format!("\n  {name}"));
+    }
+    plan
+}
+
+/// Reports what happened to each cache, and fails if anything did.
+fn summarize(done: &str, results: Vec<(String, Result<String, CliError>)>) -> Result<(), CliError> {
+    let mut outcomes: Vec<String> = Vec::new();
+    let mut failures: Vec<String> = Vec::new();
+    for (name, result) in results {
+        match result {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => failures.push(format!("  {name}: {}", e.msg)),
+        }
+    }
+    let count = |outcome: &str| outcomes.iter().filter(|o| o.as_str() == outcome).count();
+    let mut summary = format!("{} {done}", count(done));
+    let already_existed = count("already existed");
+    if already_existed > 0 {
+        summary.push_str(&format!(", {already_existed} already existed"));
+    }
+    summary.push_str(&format!(", {} failed", failures.len()));
+    console_data!("{summary}");
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::new(format!(
+            "{} cache(s) failed:\n{}",
+            failures.len(),
+            failures.join("\n")
+        )))
+    }
+}
+
+pub async fn delete_matching_caches(
+    client: CacheClient,
+    pattern: String,
+    safeguards: Safeguards,
+) -> Result<(), CliError> {
+    let regex = glob_to_regex(&pattern)?;
+    // Which caches match isn't known without listing them, so a dry run stops at the listing.
+    if skip_sdk_request("ListCaches", &[]) {
+        console_info!(
+            "Each cache matching {pattern} would then be deleted; \
+             `momento cache list` shows which caches those are"
+        );
+        return Ok(());
+    }
+    let list_result = interact_with_momento("listing caches...", client.list_caches()).await?;
+    let cache_names = list_result
+        .caches
+        .into_iter()
+        .map(|cache| cache.name)
+        .collect();
+    let (to_delete, protected) = plan_deletes(cache_names, &regex, &safeguards);
+
+    for name in &protected {
+        console_info!("Skipping {name}: it's in this profile's protected_caches; pass --force to delete it anyway");
+    }
+    if to_delete.is_empty() {
+        console_data!("No caches to delete match {pattern}");
+        return Ok(());
+    }
+    console_data!("{}", format_plan("delete", &to_delete));
+    safeguards.confirm_bulk(ResourceKind::Cache, "delete", &pattern, to_delete.len())?;
+
+    let results: Vec<_> = stream::iter(to_delete)
+        .map(|name| {
+            let client = client.clone();
+            async move {
+                let result = client
+                    .delete_cache(&name)
+                    .await
+                    .map(|_| "deleted".to_string())
+                    .map_err(CliError::from);
+                (name, result)
+            }
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+    summarize("deleted", results)
+}
+
+pub async fn create_caches_from_file(client: CacheClient, path: String) -> Result<(), CliError> {
+    let contents = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| CliError::new(format!("Could not read cache names from {path}: {e}")))?;
+    let names = parse_names(&contents);
+    if names.is_empty() {
+        return Err(CliError::new(format!("{path} has no cache names")));
+    }
+    if skip_sdk_request("CreateCache", &[("cache_name", names.join(", "))]) {
+        return Ok(());
+    }
+    console_data!("{}", format_plan("create", &names));
+
+    let results: Vec<_> = stream::iter(names)
+        .map(|name| {
+            let client = client.clone();
+            async move {
+                let result = client
+                    .create_cache(&name)
+                    .await
+                    .map(|response| match response {
+                        CreateCacheResponse::Created => "created".to_string(),
+                        CreateCacheResponse::AlreadyExists => "already existed".to_string(),
+                    })
+                    .map_err(CliError::from);
+                (name, result)
+            }
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+    summarize("created", results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn patterns_match_whole_names() {
+        let regex = glob_to_regex("momento-cli-*").expect("valid pattern");
+        assert!(regex.is_match("momento-cli-1b4e28ba-2fa1-11d2-883f-0016d3cca427"));
+        assert!(regex.is_match("momento-cli-"));
+        assert!(!regex.is_match("my-momento-cli-cache"));
+
+        let regex = glob_to_regex("test.?").expect("valid pattern");
+        assert!(regex.is_match("test.1"));
+        assert!(!regex.is_match("testx1"));
+        assert!(!regex.is_match("test.12"));
+    }
+
+    #[test]
+    fn names_file_skips_comments_blanks_and_repeats() {
+        assert_eq!(
+            parse_names("# fixtures\nalpha\n\n  beta  # for the beta suite\nalpha\n"),
+            names(&["alpha", "beta"])
+        );
+    }
+
+    #[test]
+    fn protected_caches_are_left_out_of_the_plan() {
+        let regex = glob_to_regex("momento-cli-*").expect("valid pattern");
+        let safeguards = Safeguards {
+            protected: names(&["momento-cli-keep"]),
+            ..Default::default()
+        };
+        let (to_delete, protected) = plan_deletes(
+            names(&["momento-cli-a", "momento-cli-keep", "prod"]),
+            &regex,
+            &safeguards,
+        );
+        assert_eq!(to_delete, names(&["momento-cli-a"]));
+        assert_eq!(protected, names(&["momento-cli-keep"]));
+
+        let forced = Safeguards {
+            force: true,
+            ..safeguards
+        };
+        let (to_delete, protected) = plan_deletes(
+            names(&["momento-cli-a", "momento-cli-keep"]),
+            &regex,
+            &forced,
+        );
+        assert_eq!(to_delete, names(&["momento-cli-a", "momento-cli-keep"]));
+        assert!(protected.is_empty());
+    }
+
+    #[test]
+    fn plan_lists_each_cache() {
+        assert_eq!(
+            format_plan("delete", &names(&["a", "b"])),
+            "Will delete 2 cache(s):\n  a\n  b"
+        );
+    }
+
+    #[test]
+    fn summary_fails_when_any_cache_failed() {
+        assert!(summarize(
+            "created",
+            vec![
+                ("a".to_string(), Ok("created".to_string())),
+                ("b".to_string(), Ok("already existed".to_string())),
+            ]
+        )
+        .is_ok());
+
+        let failure = summarize(
+            "deleted",
+            vec![
+                ("a".to_string(), Ok("deleted".to_string())),
+                ("b".to_string(), Err(CliError::new("NotFoundError"))),
+            ],
+        )
+        .expect_err("b failed");
+        assert_eq!(failure.msg, "1 cache(s) failed:\n  b: NotFoundError");
+    }
+}
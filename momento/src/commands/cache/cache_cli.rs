@@ -1,37 +1,174 @@
 use log::debug;
 use momento::cache::{CacheClient, GetResponse, SetRequest};
+use momento_cli_opts::OutputFormat;
+use serde::Serialize;
+use std::fmt::{self, Display};
 use std::process::exit;
 use std::time::Duration;
 
 use crate::{
     error::CliError,
-    utils::{client::interact_with_momento, console::console_data},
+    utils::{
+        client::interact_with_momento,
+        confirm::{ResourceKind, Safeguards},
+        console::console_data,
+        dry_run::skip_sdk_request,
+        trace::finish_trace,
+    },
 };
 
 pub async fn create_cache(client: CacheClient, cache_name: String) -> Result<(), CliError> {
+    if skip_sdk_request("CreateCache", &[("cache_name", cache_name.clone())]) {
+        return Ok(());
+    }
     interact_with_momento("creating cache...", client.create_cache(&cache_name))
         .await
         .map(|_| ())
 }
 
-pub async fn delete_cache(client: CacheClient, cache_name: String) -> Result<(), CliError> {
+pub async fn delete_cache(
+    client: CacheClient,
+    cache_name: String,
+    safeguards: Safeguards,
+) -> Result<(), CliError> {
+    safeguards.check(ResourceKind::Cache, "delete", &cache_name)?;
+    if skip_sdk_request("DeleteCache", &[("cache_name", cache_name.clone())]) {
+        return Ok(());
+    }
     interact_with_momento("deleting cache...", client.delete_cache(&cache_name))
         .await
         .map(|_| ())
 }
 
-pub async fn list_caches(client: CacheClient) -> Result<(), CliError> {
-    let list_result = interact_with_momento("listing caches...", client.list_caches()).await?;
+/// Per-cache limits, across the namespaces a cache holds.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CacheDescription {
+    pub name: String,
+    pub cache: CacheLimitsDescription,
+    pub topics: TopicLimitsDescription,
+}
 
-    list_result
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CacheLimitsDescription {
+    pub max_traffic_rate: u32,
+    pub max_throughput_kbps: u32,
+    pub max_item_size_kb: u32,
+    pub max_ttl_seconds: u64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TopicLimitsDescription {
+    pub max_publish_rate: u32,
+    pub max_subscription_count: u32,
+    pub max_publish_message_size_kb: u32,
+}
+
+impl Display for CacheDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        writeln!(
+            f,
+            "  cache:  {} requests/s, {} KB/s, {} KB per item, {}s max TTL",
+            self.cache.max_traffic_rate,
+            self.cache.max_throughput_kbps,
+            self.cache.max_item_size_kb,
+            self.cache.max_ttl_seconds
+        )?;
+        write!(
+            f,
+            "  topics: {} publishes/s, {} subscriptions, {} KB per message",
+            self.topics.max_publish_rate,
+            self.topics.max_subscription_count,
+            self.topics.max_publish_message_size_kb
+        )
+    }
+}
+
+async fn describe_caches(client: CacheClient) -> Result<Vec<CacheDescription>, CliError> {
+    let list_result = interact_with_momento("listing caches...", client.list_caches()).await?;
+    Ok(list_result
         .caches
         .into_iter()
-        .for_each(|cache| console_data!("{}", cache.name));
+        .map(|cache| CacheDescription {
+            name: cache.name,
+            cache: CacheLimitsDescription {
+                max_traffic_rate: cache.cache_limits.max_traffic_rate,
+                max_throughput_kbps: cache.cache_limits.max_throughput_kbps,
+                max_item_size_kb: cache.cache_limits.max_item_size_kb,
+                max_ttl_seconds: cache.cache_limits.max_ttl_seconds,
+            },
+            topics: TopicLimitsDescription {
+                max_publish_rate: cache.topic_limits.max_publish_rate,
+                max_subscription_count: cache.topic_limits.max_subscription_count,
+                max_publish_message_size_kb: cache.topic_limits.max_publish_message_size_kb,
+            },
+        })
+        .collect())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, CliError> {
+    serde_json::to_string_pretty(value)
+        .map_err(|e| CliError::new(format!("Could not serialize the output: {e}")))
+}
+
+pub async fn list_caches(
+    client: CacheClient,
+    long: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    if skip_sdk_request("ListCaches", &[]) {
+        return Ok(());
+    }
+    let caches = describe_caches(client).await?;
+
+    match (output, long) {
+        (OutputFormat::Text, false) => caches
+            .iter()
+            .for_each(|cache| console_data!("{}", cache.name)),
+        (OutputFormat::Text, true) => {
+            let descriptions: Vec<String> = caches.iter().map(ToString::to_string).collect();
+            console_data!("{}", descriptions.join("\n\n"));
+        }
+        (OutputFormat::Json, false) => {
+            let names: Vec<&str> = caches.iter().map(|cache| cache.name.as_str()).collect();
+            console_data!("{}", to_json(&names)?);
+        }
+        (OutputFormat::Json, true) => console_data!("{}", to_json(&caches)?),
+    }
+
+    Ok(())
+}
+
+pub async fn describe_cache(
+    client: CacheClient,
+    cache_name: String,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    if skip_sdk_request("ListCaches", &[]) {
+        return Ok(());
+    }
+    let cache = describe_caches(client)
+        .await?
+        .into_iter()
+        .find(|cache| cache.name == cache_name)
+        .ok_or_else(|| CliError::new(format!("Cache {cache_name} not found")))?;
 
+    match output {
+        OutputFormat::Text => console_data!("{cache}"),
+        OutputFormat::Json => console_data!("{}", to_json(&cache)?),
+    }
     Ok(())
 }
 
-pub async fn flush_cache(client: CacheClient, cache_name: String) -> Result<(), CliError> {
+pub async fn flush_cache(
+    client: CacheClient,
+    cache_name: String,
+    safeguards: Safeguards,
+) -> Result<(), CliError> {
+    safeguards.check(ResourceKind::Cache, "flush", &cache_name)?;
+    if skip_sdk_request("FlushCache", &[("cache_name", cache_name.clone())]) {
+        return Ok(());
+    }
     client.flush_cache(&cache_name).await?;
     Ok(())
 }
@@ -43,6 +180,17 @@ pub async fn set(
     value: String,
     ttl_seconds: u64,
 ) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "Set",
+        &[
+            ("cache_name", cache_name.clone()),
+            ("key", key.clone()),
+            ("value", value.clone()),
+            ("ttl_seconds", ttl_seconds.to_string()),
+        ],
+    ) {
+        return Ok(());
+    }
     debug!("setting key: {} into cache: {}", key, cache_name);
     let set_request = SetRequest::new(cache_name, key, value).ttl(Duration::from_secs(ttl_seconds));
     interact_with_momento("setting...", client.send_request(set_request))
@@ -51,6 +199,12 @@ pub async fn set(
 }
 
 pub async fn get(client: CacheClient, cache_name: String, key: String) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "Get",
+        &[("cache_name", cache_name.clone()), ("key", key.clone())],
+    ) {
+        return Ok(());
+    }
     debug!("getting key: {} from cache: {}", key, cache_name);
 
     let response = interact_with_momento("getting...", client.get(&cache_name, key)).await?;
@@ -61,6 +215,7 @@ pub async fn get(client: CacheClient, cache_name: String, key: String) -> Result
         }
         GetResponse::Miss => {
             debug!("cache miss");
+            finish_trace();
             exit(1)
         }
     };
@@ -72,8 +227,67 @@ pub async fn delete_key(
     cache_name: String,
     key: String,
 ) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "Delete",
+        &[("cache_name", cache_name.clone()), ("key", key.clone())],
+    ) {
+        return Ok(());
+    }
     debug!("deleting key: {} from cache: {}", key, cache_name);
     interact_with_momento("deleting...", client.delete(&cache_name, key))
         .await
         .map(|_| ())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn description() -> CacheDescription {
+        CacheDescription {
+            name: "my-cache".to_string(),
+            cache: CacheLimitsDescription {
+                max_traffic_rate: 100,
+                max_throughput_kbps: 1024,
+                max_item_size_kb: 4096,
+                max_ttl_seconds: 86400,
+            },
+            topics: TopicLimitsDescription {
+                max_publish_rate: 100,
+                max_subscription_count: 100,
+                max_publish_message_size_kb: 512,
+            },
+        }
+    }
+
+    #[test]
+    fn describes_limits_as_text() {
+        assert_eq!(
+            description().to_string(),
+            "my-cache
+  cache:  100 requests/s, 1024 KB/s, 4096 KB per item, 86400s max TTL
+  topics: 100 publishes/s, 100 subscriptions, 512 KB per message"
+        );
+    }
+
+    #[test]
+    fn describes_limits_as_json() {
+        assert_eq!(
+            serde_json::to_value(description()).expect("serializable"),
+            serde_json::json!({
+                "name": "my-cache",
+                "cache": {
+                    "max_traffic_rate": 100,
+                    "max_throughput_kbps": 1024,
+                    "max_item_size_kb": 4096,
+                    "max_ttl_seconds": 86400,
+                },
+                "topics": {
+                    "max_publish_rate": 100,
+                    "max_subscription_count": 100,
+                    "max_publish_message_size_kb": 512,
+                },
+            })
+        );
+    }
+}
@@ -1 +1,4 @@
+pub mod bench;
+pub mod bulk;
 pub mod cache_cli;
+pub mod ping;
@@ -0,0 +1,331 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    process::exit,
+    time::{Duration, Instant},
+};
+
+use momento::{
+    cache::{GetResponse, SetRequest},
+    CacheClient, MomentoError,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::CliError,
+    utils::{
+        console::{console_data, console_info},
+        dry_run::skip_sdk_request,
+        trace::finish_trace,
+    },
+};
+
+/// The sentinel outlives a ping that's interrupted before its delete, but not by much.
+const SENTINEL_TTL: Duration = Duration::from_secs(60);
+
+/// How many of the latest pings `--watch` summarizes.
+const WATCH_WINDOW: usize = 10;
+
+/// Why a ping found the cache unhealthy. Each reason exits with its own status.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PingFailure {
+    RequestFailed {
+        operation: &'static str,
+        error_code: String,
+        message: String,
+    },
+    WrongValue {
+        expected: String,
+        found: Option<String>,
+    },
+    TooSlow {
+        operation: &'static str,
+        latency: Duration,
+        max_latency: Duration,
+    },
+}
+
+impl PingFailure {
+    fn request_failed(operation: &'static str, error: MomentoError) -> Self {
+        PingFailure::RequestFailed {
+            operation,
+            error_code: format!("{:?}", error.error_code),
+            message: error.message,
+        }
+    }
+
+    pub fn reason(&self) -> &'static str {
+        match self {
+            PingFailure::RequestFailed { .. } => "request_failed",
+            PingFailure::WrongValue { .. } => "wrong_value",
+            PingFailure::TooSlow { .. } => "too_slow",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PingFailure::RequestFailed { .. } => 2,
+            PingFailure::WrongValue { .. } => 3,
+            PingFailure::TooSlow { .. } => 4,
+        }
+    }
+}
+
+impl Display for PingFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PingFailure::RequestFailed {
+                operation,
+                error_code,
+                message,
+            } => write!(f, "{operation} failed with {error_code}: {message}"),
+            PingFailure::WrongValue { expected, found } => match found {
+                Some(found) => write!(f, "get returned {found:?} instead of {expected:?}"),
+                None => write!(f, "get missed instead of returning {expected:?}"),
+            },
+            PingFailure::TooSlow {
+                operation,
+                latency,
+                max_latency,
+            } => write!(
+                f,
+                "{operation} took {} ms, over the {} ms limit",
+                format_millis(*latency),
+                format_millis(*max_latency)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PingTimings {
+    pub set: Duration,
+    pub get: Duration,
+    pub delete: Duration,
+}
+
+impl Display for PingTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "set {} ms, get {} ms, delete {} ms",
+            format_millis(self.set),
+            format_millis(self.get),
+            format_millis(self.delete)
+        )
+    }
+}
+
+fn format_millis(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64() * 1000.0)
+}
+
+pub async fn ping(
+    client: CacheClient,
+    cache_name: String,
+    max_latency: Option<Duration>,
+    watch: Option<Duration>,
+) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "Ping",
+        &[
+            ("cache_name", cache_name.clone()),
+            ("requests", "Set, Get, Delete of a sentinel key".to_string()),
+        ],
+    ) {
+        return Ok(());
+    }
+
+    let Some(interval) = watch else {
+        return match ping_once(&client, &cache_name, max_latency).await {
+            Ok(timings) => {
+                console_data!("OK cache {cache_name}: {timings}");
+                Ok(())
+            }
+            Err(failure) => {
+                console_info!(
+                    "UNHEALTHY ({}) cache {cache_name}: {failure}",
+                    failure.reason()
+                );
+                finish_trace();
+                exit(failure.exit_code())
+            }
+        };
+    };
+
+    let mut window = VecDeque::with_capacity(WATCH_WINDOW);
+    loop {
+        let result = ping_once(&client, &cache_name, max_latency).await;
+        if window.len() == WATCH_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(result.is_ok());
+        console_data!(
+            "{}",
+            format_status_line(
+                &chrono::Local::now().format("%H:%M:%S").to_string(),
+                &result,
+                &window
+            )
+        );
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn format_status_line(
+    time: &str,
+    result: &Result<PingTimings, PingFailure>,
+    window: &VecDeque<bool>,
+) -> String {
+    let status = match result {
+        Ok(timings) => format!("OK {timings}"),
+        Err(failure) => format!("UNHEALTHY ({}) {failure}", failure.reason()),
+    };
+    let healthy = window.iter().filter(|healthy| **healthy).count();
+    format!(
+        "{time} {status} | last {}: {healthy} ok, {} unhealthy",
+        window.len(),
+        window.len() - healthy
+    )
+}
+
+/// Sets, gets, and deletes a unique key, so concurrent pings don't see each other's values.
+async fn ping_once(
+    client: &CacheClient,
+    cache_name: &str,
+    max_latency: Option<Duration>,
+) -> Result<PingTimings, PingFailure> {
+    let key = format!("momento-cli-ping-{}", Uuid::new_v4());
+    let value = Uuid::new_v4().to_string();
+
+    let started = Instant::now();
+    client
+        .send_request(SetRequest::new(cache_name, key.as_str(), value.as_str()).ttl(SENTINEL_TTL))
+        .await
+        .map_err(|e| PingFailure::request_failed("set", e))?;
+    let set = started.elapsed();
+
+    let started = Instant::now();
+    let response = client
+        .get(cache_name, key.as_str())
+        .await
+        .map_err(|e| PingFailure::request_failed("get", e))?;
+    let get = started.elapsed();
+    let found = match response {
+        GetResponse::Hit { value } => {
+            Some(String::try_from(value).unwrap_or_else(|_| "<non-UTF-8 bytes>".to_string()))
+        }
+        GetResponse::Miss => None,
+    };
+
+    let started = Instant::now();
+    client
+        .delete(cache_name, key.as_str())
+        .await
+        .map_err(|e| PingFailure::request_failed("delete", e))?;
+    let delete = started.elapsed();
+
+    if found.as_deref() != Some(value.as_str()) {
+        return Err(PingFailure::WrongValue {
+            expected: value,
+            found,
+        });
+    }
+    let timings = PingTimings { set, get, delete };
+    check_latency(timings, max_latency)?;
+    Ok(timings)
+}
+
+fn check_latency(timings: PingTimings, max_latency: Option<Duration>) -> Result<(), PingFailure> {
+    let Some(max_latency) = max_latency else {
+        return Ok(());
+    };
+    let (operation, latency) = [
+        ("set", timings.set),
+        ("get", timings.get),
+        ("delete", timings.delete),
+    ]
+    .into_iter()
+    .max_by_key(|(_, latency)| *latency)
+    .expect("there are three timings");
+    if latency > max_latency {
+        return Err(PingFailure::TooSlow {
+            operation,
+            latency,
+            max_latency,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timings() -> PingTimings {
+        PingTimings {
+            set: Duration::from_micros(3_100),
+            get: Duration::from_micros(12_000),
+            delete: Duration::from_micros(2_500),
+        }
+    }
+
+    #[test]
+    fn slowest_request_is_checked_against_max_latency() {
+        assert_eq!(check_latency(timings(), None), Ok(()));
+        assert_eq!(
+            check_latency(timings(), Some(Duration::from_millis(20))),
+            Ok(())
+        );
+        let failure = check_latency(timings(), Some(Duration::from_millis(10))).unwrap_err();
+        assert_eq!(failure.reason(), "too_slow");
+        assert_eq!(failure.exit_code(), 4);
+        assert_eq!(
+            failure.to_string(),
+            "get took 12.0 ms, over the 10.0 ms limit"
+        );
+    }
+
+    #[test]
+    fn failures_have_distinct_exit_codes() {
+        let failures = [
+            PingFailure::RequestFailed {
+                operation: "set",
+                error_code: "NotFoundError".to_string(),
+                message: "Cache not found".to_string(),
+            },
+            PingFailure::WrongValue {
+                expected: "a".to_string(),
+                found: None,
+            },
+            check_latency(timings(), Some(Duration::from_millis(1))).unwrap_err(),
+        ];
+        let codes: Vec<i32> = failures.iter().map(PingFailure::exit_code).collect();
+        assert_eq!(codes, vec![2, 3, 4]);
+        assert_eq!(
+            failures[0].to_string(),
+            "set failed with NotFoundError: Cache not found"
+        );
+        assert_eq!(
+            failures[1].to_string(),
+            "get missed instead of returning \"a\""
+        );
+    }
+
+    #[test]
+    fn status_line_summarizes_the_window() {
+        let window = VecDeque::from([true, false, true]);
+        assert_eq!(
+            format_status_line("12:00:00", &Ok(timings()), &window),
+            "12:00:00 OK set 3.1 ms, get 12.0 ms, delete 2.5 ms | last 3: 2 ok, 1 unhealthy"
+        );
+        let failure = PingFailure::WrongValue {
+            expected: "a".to_string(),
+            found: Some("b".to_string()),
+        };
+        assert_eq!(
+            format_status_line("12:00:10", &Err(failure), &window),
+            "12:00:10 UNHEALTHY (wrong_value) get returned \"b\" instead of \"a\" | last 3: 2 ok, 1 unhealthy"
+        );
+    }
+}
@@ -186,6 +186,70 @@ impl fmt::Display for CapacityPoolResponse {
     }
 }
 
+/// What `describe --watch` shows of a pool that changed between polls.
+#[derive(Debug, PartialEq)]
+pub(super) struct PoolChanges {
+    /// Each item of the new rendering, and whether it's new or changed.
+    pub items: Vec<(String, bool)>,
+    /// Items, such as diagnostics, that were there last time and now aren't.
+    pub cleared: Vec<String>,
+}
+
+/// Splits a rendered pool into items: a line, plus the indented lines that follow it, so each
+/// diagnostic is compared as a whole.
+fn split_items(rendered: &str) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for line in rendered.lines() {
+        match items.last_mut() {
+            Some(item) if line.starts_with("  ") => {
+                item.push('\n');
+                item.push_str(line);
+            }
+            _ => items.push(line.to_string()),
+        }
+    }
+    items
+}
+
+/// The part of an item that names it, like `Status` or `- insufficient_capacity`.
+fn item_key(item: &str) -> &str {
+    let first_line = item.lines().next().unwrap_or_default();
+    first_line.split(':').next().unwrap_or(first_line)
+}
+
+pub(super) fn pool_changes(previous: Option<&str>, current: &str) -> PoolChanges {
+    let current_items = split_items(current);
+    let Some(previous) = previous else {
+        return PoolChanges {
+            items: current_items
+                .into_iter()
+                .map(|item| (item, false))
+                .collect(),
+            cleared: vec![],
+        };
+    };
+    let previous_items = split_items(previous);
+    let cleared = previous_items
+        .iter()
+        .filter(|item| {
+            !current_items
+                .iter()
+                .any(|current| item_key(current) == item_key(item))
+        })
+        .cloned()
+        .collect();
+    PoolChanges {
+        items: current_items
+            .into_iter()
+            .map(|item| {
+                let changed = !previous_items.contains(&item);
+                (item, changed)
+            })
+            .collect(),
+        cleared,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::utils::test_utils::field_map;
@@ -340,6 +404,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pool_changes_highlight_changed_fields_and_diagnostics() {
+        let previous = "Name: pool\n\
+                        Status: creating\n\
+                        Diagnostics:\n\
+                        - stuck\n  state: active\n\
+                        - slow\n  state: active";
+        let current = "Name: pool\n\
+                       Status: active\n\
+                       Diagnostics:\n\
+                       - stuck\n  state: resolved";
+
+        assert_eq!(
+            PoolChanges {
+                items: vec![
+                    ("Name: pool".to_string(), false),
+                    ("Status: active".to_string(), true),
+                    ("Diagnostics:".to_string(), false),
+                    ("- stuck\n  state: resolved".to_string(), true),
+                ],
+                cleared: vec!["- slow\n  state: active".to_string()],
+            },
+            pool_changes(Some(previous), current)
+        );
+    }
+
+    #[test]
+    fn test_pool_changes_highlight_nothing_on_first_poll() {
+        let changes = pool_changes(None, "Name: pool\nStatus: active");
+        assert!(changes.items.iter().all(|(_, changed)| !changed));
+        assert!(changes.cleared.is_empty());
+    }
+
     #[test]
     fn test_display_diagnostic_placeholder_when_empty_diagnostics() {
         assert_eq!("(none)", CapacityPoolDiagnostics(vec![]).to_string());
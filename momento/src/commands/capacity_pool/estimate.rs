@@ -0,0 +1,348 @@
+use super::utils::{CapacityPoolProvisioning, FlexProvisioning};
+use crate::{error::CliError, utils::console::console_data};
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Prices the CLI ships with. `--price-table` takes a file of the same shape, for other regions
+/// or newer prices.
+const BUNDLED_PRICES: &str = include_str!("prices.json");
+
+const HOURS_PER_MONTH: f64 = 730.0;
+
+#[derive(Debug, Deserialize)]
+struct PriceTable {
+    updated: String,
+    region: String,
+    /// Memory each node holds back for replication and overhead, so it can't hold data.
+    reserved_memory_percent: f64,
+    /// The instance type that backs flex-mode pools.
+    flex_instance_type: String,
+    instance_types: BTreeMap<String, InstancePrice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstancePrice {
+    memory_gib: f64,
+    hourly_usd: f64,
+}
+
+impl PriceTable {
+    fn instance(&self, instance_type: &str) -> Result<&InstancePrice, CliError> {
+        self.instance_types.get(instance_type).ok_or_else(|| {
+            CliError::new(format!(
+                "No price for instance type {instance_type}; the price table has {}",
+                self.instance_types
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+    }
+
+    fn usable_gib(&self, instance: &InstancePrice) -> f64 {
+        instance.memory_gib * (1.0 - self.reserved_memory_percent / 100.0)
+    }
+
+    /// The cluster that holds `capacity_gib` with the fewest nodes of `instance_type`.
+    fn cluster_for(
+        &self,
+        instance_type: &str,
+        capacity_gib: f64,
+        replicas_per_shard: u32,
+    ) -> Result<Estimate, CliError> {
+        let usable = self.usable_gib(self.instance(instance_type)?);
+        let shard_count = ((capacity_gib / usable).ceil() as u32).max(1);
+        self.cluster(instance_type, shard_count, replicas_per_shard)
+    }
+
+    fn cluster(
+        &self,
+        instance_type: &str,
+        shard_count: u32,
+        replicas_per_shard: u32,
+    ) -> Result<Estimate, CliError> {
+        let instance = self.instance(instance_type)?;
+        let nodes = shard_count * (1 + replicas_per_shard);
+        Ok(Estimate {
+            instance_type: instance_type.to_string(),
+            shard_count,
+            nodes,
+            usable_gib: shard_count as f64 * self.usable_gib(instance),
+            hourly_usd: nodes as f64 * instance.hourly_usd,
+        })
+    }
+}
+
+/// What a cluster of one instance type holds and costs.
+#[derive(Debug, PartialEq)]
+struct Estimate {
+    instance_type: String,
+    shard_count: u32,
+    nodes: u32,
+    usable_gib: f64,
+    hourly_usd: f64,
+}
+
+fn read_price_table(path: Option<&str>) -> Result<PriceTable, CliError> {
+    let Some(path) = path else {
+        return Ok(serde_json::from_str(BUNDLED_PRICES).expect("the bundled price table is valid"));
+    };
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| CliError::new(format!("Could not read --price-table {path}: {e}")))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| CliError::new(format!("Invalid price table in {path}: {e}")))
+}
+
+/// Prints what a pool would hold and cost, then the cheapest ways to get the same capacity
+/// in either mode.
+pub fn estimate_pool(
+    provisioning: CapacityPoolProvisioning,
+    price_table: Option<String>,
+) -> Result<(), CliError> {
+    let prices = read_price_table(price_table.as_deref())?;
+    let (summary, capacity_gib, replicas_per_shard) = match &provisioning {
+        CapacityPoolProvisioning::Cluster {
+            instance_type,
+            shard_count,
+            replicas_per_shard,
+            zones,
+        } => {
+            let estimate = prices.cluster(instance_type, *shard_count, *replicas_per_shard)?;
+            let summary = format!(
+                "Cluster mode: {shard_count} x {instance_type} shard(s), 1 primary + {replicas_per_shard} replica(s) each\n{}",
+                format_estimate(&estimate, zones.len())
+            );
+            (summary, estimate.usable_gib, *replicas_per_shard)
+        }
+        CapacityPoolProvisioning::Flex(flex) => (
+            format_flex(&prices, flex)?,
+            flex.capacity.max_gib as f64,
+            flex.replication.max_replicas_per_shard,
+        ),
+    };
+    console_data!("{summary}");
+    console_data!(
+        "\n{}",
+        format_options(
+            capacity_gib,
+            replicas_per_shard,
+            &compare(&prices, capacity_gib, replicas_per_shard)?
+        )
+    );
+    console_data!(
+        "\nPrices are on-demand estimates for {} from {}, updated {}",
+        prices.region,
+        price_table.as_deref().unwrap_or("the bundled price table"),
+        prices.updated
+    );
+    Ok(())
+}
+
+fn format_flex(prices: &PriceTable, flex: &FlexProvisioning) -> Result<String, CliError> {
+    let instance_type = &prices.flex_instance_type;
+    let smallest = prices.cluster_for(
+        instance_type,
+        flex.capacity.min_gib as f64,
+        flex.replication.min_replicas_per_shard,
+    )?;
+    let largest = prices.cluster_for(
+        instance_type,
+        flex.capacity.max_gib as f64,
+        flex.replication.max_replicas_per_shard,
+    )?;
+    let range = |min: String, max: String| {
+        if min == max {
+            min
+        } else {
+            format!("{min}..{max}")
+        }
+    };
+    Ok(format!(
+        "Flex mode: {}..{} GiB, {}..{} replica(s) per shard, on {instance_type} nodes\n  \
+         usable memory: {} GiB\n  \
+         nodes: {}{}\n  \
+         estimated cost: ${}/hour, ${}/month",
+        flex.capacity.min_gib,
+        flex.capacity.max_gib,
+        flex.replication.min_replicas_per_shard,
+        flex.replication.max_replicas_per_shard,
+        range(
+            format!("{:.1}", smallest.usable_gib),
+            format!("{:.1}", largest.usable_gib)
+        ),
+        range(smallest.nodes.to_string(), largest.nodes.to_string()),
+        format_zone_spread(largest.nodes, flex.zones.len()),
+        range(
+            format!("{:.2}", smallest.hourly_usd),
+            format!("{:.2}", largest.hourly_usd)
+        ),
+        range(
+            format!("{:.0}", smallest.hourly_usd * HOURS_PER_MONTH),
+            format!("{:.0}", largest.hourly_usd * HOURS_PER_MONTH)
+        ),
+    ))
+}
+
+fn format_estimate(estimate: &Estimate, zones: usize) -> String {
+    format!(
+        "  usable memory: {:.1} GiB\n  \
+         nodes: {}{}\n  \
+         estimated cost: ${:.2}/hour, ${:.0}/month",
+        estimate.usable_gib,
+        estimate.nodes,
+        format_zone_spread(estimate.nodes, zones),
+        estimate.hourly_usd,
+        estimate.hourly_usd * HOURS_PER_MONTH
+    )
+}
+
+fn format_zone_spread(nodes: u32, zones: usize) -> String {
+    match zones {
+        0 => String::new(),
+        1 => " in 1 zone".to_string(),
+        zones => format!(
+            " (up to {} per zone across {zones} zones)",
+            nodes.div_ceil(zones as u32)
+        ),
+    }
+}
+
+/// The cheapest cluster of each instance type, and flex mode pinned, that hold `capacity_gib`.
+fn compare(
+    prices: &PriceTable,
+    capacity_gib: f64,
+    replicas_per_shard: u32,
+) -> Result<Vec<(String, Estimate)>, CliError> {
+    let mut options = Vec::new();
+    for instance_type in prices.instance_types.keys() {
+        options.push((
+            "cluster".to_string(),
+            prices.cluster_for(instance_type, capacity_gib, replicas_per_shard)?,
+        ));
+    }
+    options.push((
+        "flex".to_string(),
+        prices.cluster_for(&prices.flex_instance_type, capacity_gib, replicas_per_shard)?,
+    ));
+    options.sort_by(|(_, a), (_, b)| a.hourly_usd.total_cmp(&b.hourly_usd));
+    Ok(options)
+}
+
+fn format_options(
+    capacity_gib: f64,
+    replicas_per_shard: u32,
+    options: &[(String, Estimate)],
+) -> String {
+    let mut table = format!(
+        "Ways to hold {capacity_gib:.1} GiB with 1 primary + {replicas_per_shard} replica(s) per shard, cheapest first:"
+    );
+    for (mode, option) in options {
+        table.push_str(&format!(
+            "\n  {mode:<7} {:<12} {:>3} shard(s) {:>4} node(s) {:>8.1} GiB {:>9}/hour {:>8}/month",
+            option.instance_type,
+            option.shard_count,
+            option.nodes,
+            option.usable_gib,
+            format!("${:.2}", option.hourly_usd),
+            format!("${:.0}", option.hourly_usd * HOURS_PER_MONTH)
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prices() -> PriceTable {
+        serde_json::from_str(
+            r#"{
+                "updated": "2026-01-01",
+                "region": "us-west-2",
+                "reserved_memory_percent": 25,
+                "flex_instance_type": "r7g.large",
+                "instance_types": {
+                    "r7g.large": {"memory_gib": 16, "hourly_usd": 0.25},
+                    "r7g.xlarge": {"memory_gib": 32, "hourly_usd": 0.4}
+                }
+            }"#,
+        )
+        .expect("should parse a price table")
+    }
+
+    #[test]
+    fn bundled_price_table_parses() {
+        let prices = read_price_table(None).expect("bundled prices");
+        assert!(prices.instance(&prices.flex_instance_type).is_ok());
+    }
+
+    #[test]
+    fn cluster_counts_every_replica_and_reserved_memory() {
+        assert_eq!(
+            prices().cluster("r7g.xlarge", 3, 2).expect("known type"),
+            Estimate {
+                instance_type: "r7g.xlarge".to_string(),
+                shard_count: 3,
+                nodes: 9,
+                usable_gib: 72.0,
+                hourly_usd: 3.6,
+            }
+        );
+        assert!(prices()
+            .cluster("m7g.large", 1, 0)
+            .unwrap_err()
+            .msg
+            .ends_with("the price table has r7g.large, r7g.xlarge"));
+    }
+
+    #[test]
+    fn capacity_rounds_up_to_whole_shards() {
+        let estimate = prices()
+            .cluster_for("r7g.large", 25.0, 1)
+            .expect("known type");
+        assert_eq!(estimate.shard_count, 3);
+        assert_eq!(estimate.nodes, 6);
+        assert_eq!(
+            prices()
+                .cluster_for("r7g.large", 0.0, 1)
+                .expect("known type")
+                .shard_count,
+            1
+        );
+    }
+
+    #[test]
+    fn options_are_cheapest_first() {
+        let options = compare(&prices(), 48.0, 1).expect("options");
+        let summary: Vec<(&str, &str, u32)> = options
+            .iter()
+            .map(|(mode, option)| {
+                (
+                    mode.as_str(),
+                    option.instance_type.as_str(),
+                    option.shard_count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("cluster", "r7g.xlarge", 2),
+                ("cluster", "r7g.large", 4),
+                ("flex", "r7g.large", 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn nodes_spread_across_zones() {
+        assert_eq!(format_zone_spread(9, 0), "");
+        assert_eq!(format_zone_spread(9, 1), " in 1 zone");
+        assert_eq!(
+            format_zone_spread(9, 2),
+            " (up to 5 per zone across 2 zones)"
+        );
+    }
+}
@@ -1,3 +1,7 @@
 mod display;
+pub mod estimate;
 pub mod pool_cli;
+pub mod recommend;
 pub mod utils;
+pub mod wait;
+pub mod zones;
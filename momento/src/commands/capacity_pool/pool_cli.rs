@@ -1,22 +1,42 @@
+use super::display::pool_changes;
 use super::utils::{
-    call_pool_api, call_pool_delete_api, call_pool_list_api, CapacityPoolProvisioning,
-    CapacityPoolProvisioningUpdate,
+    call_pool_api, call_pool_delete_api, call_pool_list_api, plan_spec_update,
+    CapacityPoolProvisioning, CapacityPoolProvisioningUpdate,
 };
+use super::wait::{wait_for_pool, PoolTarget};
 use crate::commands::capacity_pool::utils::ListCapacityPoolsResponse;
-use crate::commands::utils::MomentoHttpResponse::{Parsed, Unparseable};
-use crate::{error::CliError, utils::console::console_data};
+use crate::commands::utils::MomentoHttpResponse::{DryRun, Parsed, Unparseable};
+use crate::{
+    error::CliError,
+    utils::{
+        confirm::{ResourceKind, Safeguards},
+        console::{clear_screen, console_data},
+    },
+};
 
+use colored::Colorize;
 use http::Method;
 use serde_json;
+use std::io::{self, IsTerminal};
+use std::time::Duration;
 
 pub async fn create_pool(
     endpoint: String,
     auth_token: String,
     name: String,
     provisioning: CapacityPoolProvisioning,
+    wait: Option<Duration>,
 ) -> Result<(), CliError> {
     let data = serde_json::json!({"provisioning": provisioning});
-    match call_pool_api(Method::POST, endpoint, auth_token, name, Some(data)).await? {
+    match call_pool_api(
+        Method::POST,
+        endpoint.clone(),
+        auth_token.clone(),
+        name.clone(),
+        Some(data),
+    )
+    .await?
+    {
         Parsed(pool) => {
             console_data!("Creating capacity pool!\n\n{pool}");
         }
@@ -26,7 +46,11 @@ pub async fn create_pool(
                 console_data!("\n\n{response_text}");
             }
         }
+        DryRun => {}
     };
+    if let Some(timeout) = wait {
+        wait_for_pool(endpoint, auth_token, name, PoolTarget::active(), timeout).await?;
+    }
     Ok(())
 }
 
@@ -42,6 +66,7 @@ pub async fn get_status(
         Unparseable(response_text) => {
             console_data!("{response_text}");
         }
+        DryRun => {}
     };
     Ok(())
 }
@@ -50,16 +75,70 @@ pub async fn describe_pool(
     endpoint: String,
     auth_token: String,
     name: String,
+    watch: Option<Duration>,
 ) -> Result<(), CliError> {
-    match call_pool_api(Method::GET, endpoint, auth_token, name, None).await? {
-        Parsed(pool) => {
-            console_data!("Your capacity pool:\n\n{pool}");
+    let Some(interval) = watch else {
+        match call_pool_api(Method::GET, endpoint, auth_token, name, None).await? {
+            Parsed(pool) => {
+                console_data!("Your capacity pool:\n\n{pool}");
+            }
+            Unparseable(response_text) => {
+                console_data!("Your capacity pool:\n\n{response_text}");
+            }
+            DryRun => {}
+        };
+        return Ok(());
+    };
+
+    // Redraw in place on a terminal; otherwise, append each poll so the output reads as a log.
+    let redraw = io::stdout().is_terminal();
+    let mut previous: Option<String> = None;
+    loop {
+        let rendered = match call_pool_api(
+            Method::GET,
+            endpoint.clone(),
+            auth_token.clone(),
+            name.clone(),
+            None,
+        )
+        .await?
+        {
+            Parsed(pool) => pool.to_string(),
+            Unparseable(response_text) => response_text,
+            // Every poll would print the same request.
+            DryRun => return Ok(()),
+        };
+        clear_screen();
+        console_data!(
+            "Every {}: capacity pool {name}, {}\n",
+            humanize_interval(interval),
+            chrono::Local::now().format("%H:%M:%S")
+        );
+        let changes = pool_changes(previous.as_deref(), &rendered);
+        for (item, changed) in changes.items {
+            if changed {
+                console_data!("{}", item.yellow().bold());
+            } else {
+                console_data!("{item}");
+            }
         }
-        Unparseable(response_text) => {
-            console_data!("Your capacity pool:\n\n{response_text}");
+        for item in changes.cleared {
+            console_data!("{}", format!("{item} (cleared)").dimmed());
         }
-    };
-    Ok(())
+        if !redraw {
+            console_data!("");
+        }
+        previous = Some(rendered);
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn humanize_interval(interval: Duration) -> String {
+    if interval.subsec_millis() == 0 {
+        format!("{}s", interval.as_secs())
+    } else {
+        format!("{}ms", interval.as_millis())
+    }
 }
 
 pub async fn update_pool(
@@ -67,9 +146,18 @@ pub async fn update_pool(
     auth_token: String,
     name: String,
     provisioning_update: CapacityPoolProvisioningUpdate,
+    wait: Option<Duration>,
 ) -> Result<(), CliError> {
     let data = serde_json::json!({"provisioning": provisioning_update});
-    match call_pool_api(Method::PATCH, endpoint, auth_token, name, Some(data)).await? {
+    match call_pool_api(
+        Method::PATCH,
+        endpoint.clone(),
+        auth_token.clone(),
+        name.clone(),
+        Some(data),
+    )
+    .await?
+    {
         Parsed(mut pool) => {
             pool.hide_lagging_target(provisioning_update);
             console_data!("Updating capacity pool!\n\n{pool}");
@@ -80,19 +168,72 @@ pub async fn update_pool(
                 console_data!("\n\n{response_text}");
             }
         }
+        DryRun => {}
     };
+    if let Some(timeout) = wait {
+        wait_for_pool(endpoint, auth_token, name, PoolTarget::active(), timeout).await?;
+    }
     Ok(())
 }
 
+/// Updates a pool to match a spec, showing what will change first. Only the changed fields are
+/// sent, so fields the spec leaves as they are can't race with other updates.
+pub async fn update_pool_from_spec(
+    endpoint: String,
+    auth_token: String,
+    name: String,
+    spec: CapacityPoolProvisioning,
+    wait: Option<Duration>,
+) -> Result<(), CliError> {
+    let live = match call_pool_api(
+        Method::GET,
+        endpoint.clone(),
+        auth_token.clone(),
+        name.clone(),
+        None,
+    )
+    .await?
+    {
+        Parsed(pool) => pool,
+        Unparseable(response_text) => {
+            return Err(CliError::new(format!(
+                "Can't compare the spec with capacity pool {name}; couldn't parse it"
+            ))
+            .with_details(response_text))
+        }
+        // There's no live pool to compare with, so show the update that sets everything.
+        DryRun => return update_pool(endpoint, auth_token, name, spec.into(), wait).await,
+    };
+    let (changes, provisioning_update) = plan_spec_update(&live.provisioning, spec)?;
+    if changes.is_empty() {
+        console_data!("Capacity pool {name} already matches the spec");
+        return Ok(());
+    }
+    console_data!(
+        "Changes to capacity pool {name}:\n- {}\n",
+        changes.join("\n- ")
+    );
+    update_pool(endpoint, auth_token, name, provisioning_update, wait).await
+}
+
 pub async fn delete_pool(
     endpoint: String,
     auth_token: String,
     name: String,
+    safeguards: Safeguards,
+    wait: Option<Duration>,
 ) -> Result<(), CliError> {
-    let response_text = call_pool_delete_api(endpoint, auth_token, name.clone()).await?;
-    console_data!("Deleting capacity pool {name}!");
-    if !response_text.is_empty() {
-        console_data!("\n\n{response_text}");
+    safeguards.check(ResourceKind::CapacityPool, "delete", &name)?;
+    if let Some(response_text) =
+        call_pool_delete_api(endpoint.clone(), auth_token.clone(), name.clone()).await?
+    {
+        console_data!("Deleting capacity pool {name}!");
+        if !response_text.is_empty() {
+            console_data!("\n\n{response_text}");
+        }
+    }
+    if let Some(timeout) = wait {
+        wait_for_pool(endpoint, auth_token, name, PoolTarget::Deleted, timeout).await?;
     }
     Ok(())
 }
@@ -115,6 +256,7 @@ pub async fn list_pools(endpoint: String, auth_token: String) -> Result<(), CliE
         Unparseable(response_text) => {
             console_data!("Listing your capacity pools:\n\n{response_text}");
         }
+        DryRun => {}
     };
     Ok(())
 }
@@ -0,0 +1,449 @@
+use super::utils::{CapacityBounds, CapacityPoolProvisioning, FlexProvisioning, ReplicationBounds};
+use super::zones::resolve_zones;
+use crate::commands::cloud_linter::resource::{ElastiCacheResource, ResourceType};
+use crate::{
+    error::CliError,
+    utils::console::{console_data, console_info},
+};
+use momento_cli_opts::{AvailabilityZone, CapacityPoolProvisioningMode};
+
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+/// How far past its peak usage a flex-mode pool may grow.
+const FLEX_HEADROOM: f64 = 2.0;
+
+const GIB: f64 = (1u64 << 30) as f64;
+
+/// The linter's output: a mix of resource kinds, of which only ElastiCache nodes matter here.
+#[derive(Debug, Deserialize)]
+struct LinterResults {
+    resources: Vec<serde_json::Value>,
+}
+
+fn peak(node: &ElastiCacheResource, metric: &str) -> Option<f64> {
+    node.metrics
+        .iter()
+        .filter(|m| m.name == metric)
+        .flat_map(|m| m.values.iter().copied())
+        .reduce(f64::max)
+}
+
+/// The shard a node belongs to: `0001` for `my-cluster-0001-002` in a cluster-mode-enabled
+/// replication group, and the only shard otherwise.
+fn shard(node: &ElastiCacheResource) -> String {
+    if !node.metadata.cluster_mode_enabled {
+        return "0001".to_string();
+    }
+    let node_id = node
+        .id
+        .strip_prefix(&format!("{}-", node.metadata.cluster_id))
+        .unwrap_or(&node.id);
+    match node_id.split_once('-') {
+        Some((shard, _)) => shard.to_string(),
+        None => "0001".to_string(),
+    }
+}
+
+/// One ElastiCache cluster, pieced together from the linter's per-node records.
+#[derive(Debug, PartialEq)]
+struct ClusterUsage {
+    cluster_id: String,
+    region: String,
+    engine: String,
+    instance_type: String,
+    /// Whether the source is sharded, which its clients then know how to follow.
+    cluster_mode_enabled: bool,
+    /// Nodes per shard, with the peak bytes each held, if the linter got that metric.
+    shards: BTreeMap<String, Vec<Option<f64>>>,
+    zone_names: BTreeSet<String>,
+    peak_engine_cpu: Option<f64>,
+}
+
+impl ClusterUsage {
+    fn replicas_per_shard(&self) -> u32 {
+        let nodes = self.shards.values().map(Vec::len).max().unwrap_or(1);
+        nodes.saturating_sub(1) as u32
+    }
+
+    /// Peak data held across shards: each shard's busiest node, summed.
+    fn peak_data_gib(&self) -> Option<f64> {
+        self.shards
+            .values()
+            .map(|nodes| nodes.iter().flatten().copied().reduce(f64::max))
+            .sum::<Option<f64>>()
+            .map(|bytes| bytes / GIB)
+    }
+
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} ({} in {}, cluster mode {}): {} shard(s) of 1 primary + {} replica(s) on {}",
+            self.cluster_id,
+            self.engine,
+            self.region,
+            if self.cluster_mode_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            },
+            self.shards.len(),
+            self.replicas_per_shard(),
+            self.instance_type
+        );
+        if let Some(data) = self.peak_data_gib() {
+            summary.push_str(&format!(", peak {data:.1} GiB used"));
+        }
+        if let Some(cpu) = self.peak_engine_cpu {
+            summary.push_str(&format!(", peak engine CPU {cpu:.0}%"));
+        }
+        summary
+    }
+}
+
+fn read_linter_results(path: &str) -> Result<LinterResults, CliError> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| CliError::new(format!("Could not read linter results {path}: {e}")))?;
+    let mut contents = String::new();
+    let read = if path.ends_with(".gz") {
+        GzDecoder::new(file).read_to_string(&mut contents)
+    } else {
+        std::io::BufReader::new(file).read_to_string(&mut contents)
+    };
+    read.map_err(|e| CliError::new(format!("Could not read linter results {path}: {e}")))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| CliError::new(format!("Invalid linter results in {path}: {e}")))
+}
+
+/// Groups the linter's Redis and Valkey node records by cluster, skipping everything else.
+fn cluster_usage(results: LinterResults) -> Result<Vec<ClusterUsage>, CliError> {
+    let mut clusters: BTreeMap<(String, String), ClusterUsage> = BTreeMap::new();
+    for resource in results.resources {
+        let is_migratable = matches!(
+            serde_json::from_value(resource["type"].clone()),
+            Ok(ResourceType::ElastiCacheRedisNode | ResourceType::ElastiCacheValkeyNode)
+        );
+        if !is_migratable {
+            continue;
+        }
+        let node: ElastiCacheResource = serde_json::from_value(resource)
+            .map_err(|e| CliError::new(format!("Invalid ElastiCache record: {e}")))?;
+        let cluster = clusters
+            .entry((node.region.clone(), node.metadata.cluster_id.clone()))
+            .or_insert_with(|| ClusterUsage {
+                cluster_id: node.metadata.cluster_id.clone(),
+                region: node.region.clone(),
+                engine: node.metadata.engine.clone(),
+                instance_type: node
+                    .metadata
+                    .cache_node_type
+                    .trim_start_matches("cache.")
+                    .to_string(),
+                cluster_mode_enabled: node.metadata.cluster_mode_enabled,
+                shards: BTreeMap::new(),
+                zone_names: BTreeSet::new(),
+                peak_engine_cpu: None,
+            });
+        cluster
+            .shards
+            .entry(shard(&node))
+            .or_default()
+            .push(peak(&node, "BytesUsedForCache"));
+        cluster
+            .zone_names
+            .insert(node.metadata.preferred_az.clone());
+        cluster.peak_engine_cpu = [cluster.peak_engine_cpu, peak(&node, "EngineCPUUtilization")]
+            .into_iter()
+            .flatten()
+            .reduce(f64::max);
+    }
+    Ok(clusters.into_values().collect())
+}
+
+/// Proposes a pool for a cluster: flex mode sized from its peak usage when the linter collected
+/// memory metrics, and a like-for-like cluster-mode pool otherwise. A cluster-mode-disabled
+/// source also gets a cluster-mode pool, since its clients can't follow a flex pool's resharding.
+fn recommend(
+    cluster: &ClusterUsage,
+    mode: Option<&CapacityPoolProvisioningMode>,
+    zones: Vec<String>,
+) -> Result<CapacityPoolProvisioning, CliError> {
+    let data_gib = cluster.peak_data_gib();
+    let mode = match (mode, data_gib) {
+        (Some(mode), _) => mode.clone(),
+        (None, Some(_)) if cluster.cluster_mode_enabled => CapacityPoolProvisioningMode::Flex,
+        (None, _) => CapacityPoolProvisioningMode::Cluster,
+    };
+    let replicas_per_shard = cluster.replicas_per_shard();
+    match mode {
+        CapacityPoolProvisioningMode::Cluster => Ok(CapacityPoolProvisioning::Cluster {
+            instance_type: cluster.instance_type.clone(),
+            shard_count: cluster.shards.len() as u32,
+            replicas_per_shard,
+            zones,
+        }),
+        CapacityPoolProvisioningMode::Flex => {
+            let data_gib = data_gib.ok_or_else(|| {
+                CliError::new(
+                    "the linter collected no BytesUsedForCache metrics to size a flex-mode pool from",
+                )
+            })?;
+            let min_gib = (data_gib.ceil() as u32).max(1);
+            let max_gib = ((data_gib * FLEX_HEADROOM).ceil() as u32).max(min_gib);
+            Ok(CapacityPoolProvisioning::Flex(FlexProvisioning {
+                capacity: CapacityBounds { min_gib, max_gib },
+                replication: ReplicationBounds {
+                    min_replicas_per_shard: replicas_per_shard,
+                    max_replicas_per_shard: replicas_per_shard,
+                },
+                zones,
+            }))
+        }
+    }
+}
+
+/// Prints a `pool create --spec` for each Redis or Valkey cluster in the linter's results,
+/// and writes them to `output_dir` as `<cluster>.json` if it's given.
+pub async fn recommend_pools(
+    from: String,
+    mode: Option<CapacityPoolProvisioningMode>,
+    translate_zone_names: bool,
+    aws_profile: Option<String>,
+    output_dir: Option<String>,
+) -> Result<(), CliError> {
+    let clusters = cluster_usage(read_linter_results(&from)?)?;
+    if clusters.is_empty() {
+        console_data!("{from} has no ElastiCache Redis or Valkey clusters to migrate");
+        return Ok(());
+    }
+
+    let zone_names: BTreeSet<String> = clusters
+        .iter()
+        .flat_map(|cluster| cluster.zone_names.iter().cloned())
+        .collect();
+    let zone_ids: HashMap<String, String> = if translate_zone_names {
+        let names: Vec<String> = zone_names.into_iter().collect();
+        let ids = resolve_zones(
+            names.iter().cloned().map(AvailabilityZone::Name).collect(),
+            true,
+            aws_profile,
+        )
+        .await?;
+        names.into_iter().zip(ids).collect()
+    } else {
+        console_info!(
+            "The linter records availability zone names; pass --translate-zone-names to put \
+             zone IDs in these specs, which `pool create --spec` needs"
+        );
+        HashMap::new()
+    };
+
+    for cluster in &clusters {
+        let zones = cluster
+            .zone_names
+            .iter()
+            .map(|name| zone_ids.get(name).unwrap_or(name).clone())
+            .collect();
+        console_data!("\n# {}", cluster.summary());
+        let provisioning = match recommend(cluster, mode.as_ref(), zones) {
+            Ok(provisioning) => provisioning,
+            Err(e) => {
+                console_data!("# Skipped: {}", e.msg);
+                continue;
+            }
+        };
+        let spec =
+            serde_json::to_string_pretty(&provisioning).expect("provisioning always serializes");
+        console_data!("{spec}");
+        if let Some(dir) = &output_dir {
+            let path = Path::new(dir).join(format!("{}.json", cluster.cluster_id));
+            std::fs::write(&path, format!("{spec}\n"))
+                .map_err(|e| CliError::new(format!("Could not write {}: {e}", path.display())))?;
+            console_info!("Wrote {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn node(cluster_id: &str, id: &str, az: &str, peak_bytes: Option<f64>) -> serde_json::Value {
+        let metrics = match peak_bytes {
+            Some(bytes) => json!([
+                {"name": "BytesUsedForCache", "values": [bytes / 2.0, bytes]},
+                {"name": "EngineCPUUtilization", "values": [12.0, 41.5]}
+            ]),
+            None => json!([]),
+        };
+        json!({
+            "type": "AWS::Elasticache::RedisNode",
+            "region": "us-east-1",
+            "id": id,
+            "metrics": metrics,
+            "metricPeriodSeconds": 3600,
+            "metadata": {
+                "clusterId": cluster_id,
+                "engine": "redis",
+                "cacheNodeType": "cache.r7g.xlarge",
+                "preferredAz": az,
+                "clusterModeEnabled": id.matches('-').count() == 2
+            }
+        })
+    }
+
+    fn results(resources: Vec<serde_json::Value>) -> LinterResults {
+        LinterResults { resources }
+    }
+
+    #[test]
+    fn nodes_are_grouped_into_shards_of_each_cluster() {
+        let clusters = cluster_usage(results(vec![
+            node(
+                "sessions",
+                "sessions-0001-001",
+                "us-east-1a",
+                Some(4.0 * GIB),
+            ),
+            node(
+                "sessions",
+                "sessions-0001-002",
+                "us-east-1b",
+                Some(3.0 * GIB),
+            ),
+            node(
+                "sessions",
+                "sessions-0002-001",
+                "us-east-1b",
+                Some(2.0 * GIB),
+            ),
+            node(
+                "sessions",
+                "sessions-0002-002",
+                "us-east-1a",
+                Some(2.5 * GIB),
+            ),
+            node("legacy", "legacy-001", "us-east-1c", None),
+            json!({"type": "AWS::DynamoDB::Table", "region": "us-east-1", "id": "orders"}),
+            json!({"type": "AWS::Elasticache::MemcachedNode", "region": "us-east-1", "id": "0001"}),
+        ]))
+        .expect("valid records");
+        assert_eq!(clusters.len(), 2);
+
+        let legacy = &clusters[0];
+        assert_eq!(legacy.cluster_id, "legacy");
+        assert_eq!(legacy.shards.len(), 1);
+        assert_eq!(legacy.replicas_per_shard(), 0);
+        assert_eq!(legacy.peak_data_gib(), None);
+
+        let sessions = &clusters[1];
+        assert_eq!(sessions.instance_type, "r7g.xlarge");
+        assert_eq!(sessions.shards.len(), 2);
+        assert_eq!(sessions.replicas_per_shard(), 1);
+        assert_eq!(sessions.peak_data_gib(), Some(6.5));
+        assert_eq!(sessions.peak_engine_cpu, Some(41.5));
+        assert_eq!(
+            sessions.zone_names.iter().collect::<Vec<_>>(),
+            vec!["us-east-1a", "us-east-1b"]
+        );
+    }
+
+    #[test]
+    fn flex_pools_are_sized_from_peak_usage() {
+        let clusters = cluster_usage(results(vec![
+            node(
+                "sessions",
+                "sessions-0001-001",
+                "us-east-1a",
+                Some(4.0 * GIB),
+            ),
+            node(
+                "sessions",
+                "sessions-0001-002",
+                "us-east-1b",
+                Some(3.0 * GIB),
+            ),
+            node(
+                "sessions",
+                "sessions-0002-001",
+                "us-east-1b",
+                Some(2.5 * GIB),
+            ),
+        ]))
+        .expect("valid records");
+        let zones = vec!["use1-az1".to_string(), "use1-az2".to_string()];
+        let CapacityPoolProvisioning::Flex(flex) =
+            recommend(&clusters[0], None, zones.clone()).expect("recommendation")
+        else {
+            panic!("expected flex mode");
+        };
+        assert_eq!(
+            flex.capacity,
+            CapacityBounds {
+                min_gib: 7,
+                max_gib: 13
+            }
+        );
+        assert_eq!(flex.replication.max_replicas_per_shard, 1);
+        assert_eq!(flex.zones, zones);
+    }
+
+    #[test]
+    fn cluster_mode_disabled_sources_get_one_shard_cluster_pools() {
+        let mut nodes = vec![
+            node("orders-rg", "orders-rg-001", "us-east-1a", Some(3.0 * GIB)),
+            node("orders-rg", "orders-rg-002", "us-east-1b", Some(3.0 * GIB)),
+        ];
+        for node in &mut nodes {
+            node["metadata"]["clusterModeEnabled"] = json!(false);
+        }
+        let clusters = cluster_usage(results(nodes)).expect("valid records");
+        assert_eq!(clusters[0].shards.len(), 1);
+        assert_eq!(clusters[0].replicas_per_shard(), 1);
+        assert!(matches!(
+            recommend(&clusters[0], None, vec![]).expect("recommendation"),
+            CapacityPoolProvisioning::Cluster {
+                shard_count: 1,
+                replicas_per_shard: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn cluster_pools_match_the_existing_cluster() {
+        let clusters = cluster_usage(results(vec![node(
+            "legacy",
+            "legacy-001",
+            "us-east-1c",
+            None,
+        )]))
+        .expect("valid records");
+        let zones = vec!["use1-az4".to_string()];
+        match recommend(&clusters[0], None, zones).expect("recommendation") {
+            CapacityPoolProvisioning::Cluster {
+                instance_type,
+                shard_count,
+                replicas_per_shard,
+                ..
+            } => {
+                assert_eq!(instance_type, "r7g.xlarge");
+                assert_eq!(shard_count, 1);
+                assert_eq!(replicas_per_shard, 0);
+            }
+            other => panic!("expected cluster mode, got {other:?}"),
+        }
+        assert!(recommend(
+            &clusters[0],
+            Some(&CapacityPoolProvisioningMode::Flex),
+            vec![]
+        )
+        .unwrap_err()
+        .msg
+        .contains("BytesUsedForCache"));
+    }
+}
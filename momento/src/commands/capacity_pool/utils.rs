@@ -1,19 +1,24 @@
 use crate::commands::utils::{
-    call_momento_http_api, call_momento_http_api_raw, MomentoHttpData, MomentoHttpResponse,
+    call_momento_http_api, call_momento_http_api_full, call_momento_http_api_raw,
+    idempotency_headers, success_text, MomentoHttpData, MomentoHttpResponse,
 };
 use crate::error::CliError;
-use momento_cli_opts::{Bounds, CapacityPoolProvisioningMode};
+use momento_cli_opts::{
+    parse_availability_zone, validate_shard_count, AvailabilityZone, Bounds,
+    CapacityPoolProvisioningMode,
+};
 
-use http::Method;
-use serde::{Deserialize, Serialize};
+use http::{Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::Path;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CapacityBounds {
     pub min_gib: u32,
     pub max_gib: u32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ReplicationBounds {
     pub min_replicas_per_shard: u32,
     pub max_replicas_per_shard: u32,
@@ -286,6 +291,211 @@ pub fn determine_provisioning_update(
     Ok(update)
 }
 
+/// Reads a `--spec` file, as YAML if it's named `.yaml` or `.yml` and as JSON otherwise.
+fn read_spec<T: DeserializeOwned>(path: &str) -> Result<T, CliError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| CliError::new(format!("Could not read --spec {path}: {e}")))?;
+    let is_yaml = matches!(
+        Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str()),
+        Some("yaml" | "yml")
+    );
+    let invalid =
+        |e: &dyn std::fmt::Display| CliError::new(format!("Invalid pool spec in {path}: {e}"));
+    // serde_yaml would want the provisioning mode as a `!managed` tag; going through a JSON value
+    // lets YAML specs use the same `managed:` key as JSON ones.
+    let value: serde_json::Value = if is_yaml {
+        serde_yaml::from_str(&contents).map_err(|e| invalid(&e))?
+    } else {
+        serde_json::from_str(&contents).map_err(|e| invalid(&e))?
+    };
+    serde_json::from_value(value).map_err(|e| invalid(&e))
+}
+
+/// Reads and validates a pool's provisioning from a `--spec` file, which takes the same shape
+/// as the API: `{"managed": {...}}` for flex mode or `{"explicit": {...}}` for cluster mode.
+pub fn read_provisioning_spec(path: &str) -> Result<CapacityPoolProvisioning, CliError> {
+    let provisioning = read_spec(path)?;
+    validate_provisioning(&provisioning)
+        .map_err(|e| CliError::new(format!("Invalid pool spec in {path}: {}", e.msg)))?;
+    Ok(provisioning)
+}
+
+/// Applies the rules the CLI flags' parsers enforce to provisioning that didn't come from flags.
+/// Cluster mode's replicas per shard is always pinned, since the spec only has room for one.
+fn validate_provisioning(provisioning: &CapacityPoolProvisioning) -> Result<(), CliError> {
+    let field = |name: &'static str| move |e: String| CliError::new(format!("{name}: {e}"));
+    match provisioning {
+        CapacityPoolProvisioning::Cluster {
+            shard_count, zones, ..
+        } => {
+            validate_shard_count(*shard_count).map_err(field("shard_count"))?;
+            validate_zone_ids(zones)
+        }
+        CapacityPoolProvisioning::Flex(provisioning) => {
+            let capacity = &provisioning.capacity;
+            Bounds {
+                min: capacity.min_gib,
+                max: capacity.max_gib,
+            }
+            .validate_positive()
+            .map_err(field("capacity"))?;
+            let replication = &provisioning.replication;
+            Bounds {
+                min: replication.min_replicas_per_shard,
+                max: replication.max_replicas_per_shard,
+            }
+            .validate()
+            .map_err(field("replication"))?;
+            validate_zone_ids(&provisioning.zones)
+        }
+    }
+}
+
+/// Applies `--zones`' parser to each zone, and rejects zone names like `us-west-2a` as well;
+/// the API takes zone IDs like `usw2-az1`.
+fn validate_zone_ids(zones: &[String]) -> Result<(), CliError> {
+    if zones.is_empty() {
+        return Err(CliError::new(
+            "zones must list at least one availability zone ID",
+        ));
+    }
+    for zone in zones {
+        match parse_availability_zone(zone).map_err(CliError::new)? {
+            AvailabilityZone::Id(_) => {}
+            AvailabilityZone::Name(_) => {
+                return Err(CliError::new(format!(
+                    "{zone} is an availability zone name; zones must be zone IDs, like usw2-az1"
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+impl From<CapacityPoolProvisioning> for CapacityPoolProvisioningUpdate {
+    /// An update that sets every field, for when there's no live pool to compare with.
+    fn from(provisioning: CapacityPoolProvisioning) -> Self {
+        match provisioning {
+            CapacityPoolProvisioning::Cluster {
+                instance_type,
+                shard_count,
+                replicas_per_shard,
+                zones,
+            } => Self::Cluster {
+                instance_type: Some(instance_type),
+                shard_count: Some(shard_count),
+                replicas_per_shard: Some(replicas_per_shard),
+                zones,
+            },
+            CapacityPoolProvisioning::Flex(provisioning) => Self::Flex {
+                capacity: Some(provisioning.capacity),
+                replication: Some(provisioning.replication),
+                zones: provisioning.zones,
+            },
+        }
+    }
+}
+
+/// Compares a spec with the live pool's provisioning, returning a line per changed field and
+/// an update that changes just those fields.
+pub fn plan_spec_update(
+    live: &CapacityPoolProvisioning,
+    desired: CapacityPoolProvisioning,
+) -> Result<(Vec<String>, CapacityPoolProvisioningUpdate), CliError> {
+    fn change<T: PartialEq>(
+        changes: &mut Vec<String>,
+        field: &str,
+        live: &T,
+        desired: T,
+        show: impl Fn(&T) -> String,
+    ) -> Option<T> {
+        (*live != desired).then(|| {
+            changes.push(format!("{field}: {} -> {}", show(live), show(&desired)));
+            desired
+        })
+    }
+    let zones = |zones: &Vec<String>| zones.join(", ");
+    let mut changes = Vec::new();
+    let update = match (live, desired) {
+        (
+            CapacityPoolProvisioning::Cluster {
+                instance_type: live_instance_type,
+                shard_count: live_shard_count,
+                replicas_per_shard: live_replicas_per_shard,
+                zones: live_zones,
+            },
+            CapacityPoolProvisioning::Cluster {
+                instance_type,
+                shard_count,
+                replicas_per_shard,
+                zones: desired_zones,
+            },
+        ) => CapacityPoolProvisioningUpdate::Cluster {
+            instance_type: change(
+                &mut changes,
+                "instance type",
+                live_instance_type,
+                instance_type,
+                String::clone,
+            ),
+            shard_count: change(
+                &mut changes,
+                "shard count",
+                live_shard_count,
+                shard_count,
+                u32::to_string,
+            ),
+            replicas_per_shard: change(
+                &mut changes,
+                "replicas per shard",
+                live_replicas_per_shard,
+                replicas_per_shard,
+                u32::to_string,
+            ),
+            zones: change(&mut changes, "zones", live_zones, desired_zones, zones)
+                .unwrap_or_default(),
+        },
+        (CapacityPoolProvisioning::Flex(live), CapacityPoolProvisioning::Flex(desired)) => {
+            CapacityPoolProvisioningUpdate::Flex {
+                capacity: change(
+                    &mut changes,
+                    "capacity",
+                    &live.capacity,
+                    desired.capacity,
+                    |capacity| format!("{}..{} GiB", capacity.min_gib, capacity.max_gib),
+                ),
+                replication: change(
+                    &mut changes,
+                    "replication",
+                    &live.replication,
+                    desired.replication,
+                    |replication| {
+                        format!(
+                            "{}..{} per shard",
+                            replication.min_replicas_per_shard, replication.max_replicas_per_shard
+                        )
+                    },
+                ),
+                zones: change(&mut changes, "zones", &live.zones, desired.zones, zones)
+                    .unwrap_or_default(),
+            }
+        }
+        (live, _) => {
+            let (spec_mode, live_mode) = match live {
+                CapacityPoolProvisioning::Cluster { .. } => ("flex", "cluster"),
+                CapacityPoolProvisioning::Flex(_) => ("cluster", "flex"),
+            };
+            return Err(CliError::new(format!(
+                "The spec is for a {spec_mode}-mode pool, but the pool is in {live_mode} mode; \
+                 a spec can't change the mode"
+            )));
+        }
+    };
+    Ok((changes, update))
+}
+
 fn build_request_url(endpoint: String, pool_name: Option<String>) -> String {
     match pool_name {
         None => format!("{endpoint}/capacity_pool"),
@@ -300,21 +510,51 @@ pub async fn call_pool_api(
     pool_name: String,
     data: Option<serde_json::Value>,
 ) -> Result<MomentoHttpResponse<CapacityPoolResponse>, CliError> {
+    // Creates and updates carry an idempotency key, so they're safe to retry.
+    let headers = matches!(method, Method::POST | Method::PATCH).then(idempotency_headers);
     call_momento_http_api(
         method,
         build_request_url(endpoint, Some(pool_name)),
         auth_token,
-        None,
+        headers,
         data.map(MomentoHttpData::Json),
     )
     .await
 }
 
+/// Like a GET through `call_pool_api`, but a pool that doesn't exist is `Parsed(None)` instead
+/// of an error, for callers waiting on a delete.
+pub async fn call_pool_get_api(
+    endpoint: String,
+    auth_token: String,
+    pool_name: String,
+) -> Result<MomentoHttpResponse<Option<CapacityPoolResponse>>, CliError> {
+    let Some(response) = call_momento_http_api_full(
+        Method::GET,
+        build_request_url(endpoint, Some(pool_name)),
+        auth_token,
+        None,
+        None,
+    )
+    .await?
+    else {
+        return Ok(MomentoHttpResponse::DryRun);
+    };
+    if response.status == StatusCode::NOT_FOUND {
+        return Ok(MomentoHttpResponse::Parsed(None));
+    }
+    let response_text = success_text(response)?;
+    Ok(match serde_json::from_str(&response_text) {
+        Ok(pool) => MomentoHttpResponse::Parsed(Some(pool)),
+        Err(_) => MomentoHttpResponse::Unparseable(response_text),
+    })
+}
+
 pub async fn call_pool_delete_api(
     endpoint: String,
     auth_token: String,
     pool_name: String,
-) -> Result<String, CliError> {
+) -> Result<Option<String>, CliError> {
     call_momento_http_api_raw(
         Method::DELETE,
         build_request_url(endpoint, Some(pool_name)),
@@ -1465,4 +1705,148 @@ mod tests {
             pool.extra_fields
         );
     }
+
+    // ========== ========== ===========
+    // --spec files
+    // ========== ========== ===========
+
+    fn write_spec(dir: &tempdir::TempDir, file_name: &str, contents: &str) -> String {
+        let path = dir.path().join(file_name);
+        std::fs::write(&path, contents).expect("should write spec");
+        path.to_string_lossy().to_string()
+    }
+
+    fn flex(min_gib: u32, max_gib: u32, zones: Vec<String>) -> CapacityPoolProvisioning {
+        CapacityPoolProvisioning::Flex(FlexProvisioning {
+            capacity: CapacityBounds { min_gib, max_gib },
+            replication: ReplicationBounds {
+                min_replicas_per_shard: 1,
+                max_replicas_per_shard: 2,
+            },
+            zones,
+        })
+    }
+
+    #[test]
+    fn test_read_provisioning_spec_from_yaml_and_json() {
+        let dir = tempdir::TempDir::new("pool-spec").expect("should create temp dir");
+        let yaml = write_spec(
+            &dir,
+            "pool.yaml",
+            "managed:\n  \
+               capacity: {min_gib: 32, max_gib: 128}\n  \
+               replication: {min_replicas_per_shard: 1, max_replicas_per_shard: 2}\n  \
+               zones: [use1-az1, use1-az2]\n",
+        );
+        let CapacityPoolProvisioning::Flex(provisioning) =
+            read_provisioning_spec(&yaml).expect("YAML spec should parse")
+        else {
+            panic!("expected flex provisioning");
+        };
+        assert_eq!(128, provisioning.capacity.max_gib);
+        assert_eq!(strings(["use1-az1", "use1-az2"]), provisioning.zones);
+
+        let json = write_spec(
+            &dir,
+            "pool.json",
+            r#"{"explicit": {"instance_type": "r7g.xlarge", "shard_count": 3,
+                "replicas_per_shard": 2, "zones": ["use1-az1"]}}"#,
+        );
+        let provisioning = read_provisioning_spec(&json).expect("JSON spec should parse");
+        assert!(matches!(
+            provisioning,
+            CapacityPoolProvisioning::Cluster { shard_count: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_read_provisioning_spec_applies_flag_rules() {
+        let dir = tempdir::TempDir::new("pool-spec").expect("should create temp dir");
+        for (contents, want) in [
+            (
+                r#"{"managed": {"capacity": {"min_gib": 0, "max_gib": 128},
+                    "replication": {"min_replicas_per_shard": 1, "max_replicas_per_shard": 2},
+                    "zones": ["use1-az1"]}}"#,
+                "capacity: bounds must be >0",
+            ),
+            (
+                r#"{"managed": {"capacity": {"min_gib": 256, "max_gib": 128},
+                    "replication": {"min_replicas_per_shard": 1, "max_replicas_per_shard": 2},
+                    "zones": ["use1-az1"]}}"#,
+                "capacity: bounds are inverted: 256..128",
+            ),
+            (
+                r#"{"managed": {"capacity": {"min_gib": 32, "max_gib": 128},
+                    "replication": {"min_replicas_per_shard": 3, "max_replicas_per_shard": 2},
+                    "zones": ["use1-az1"]}}"#,
+                "replication: bounds are inverted: 3..2",
+            ),
+            (
+                r#"{"explicit": {"instance_type": "r7g.xlarge", "shard_count": 0,
+                    "replicas_per_shard": 2, "zones": ["use1-az1"]}}"#,
+                "shard_count: shard count must be >0",
+            ),
+            (
+                r#"{"explicit": {"instance_type": "r7g.xlarge", "shard_count": 3,
+                    "replicas_per_shard": 2, "zones": ["us-east-1a"]}}"#,
+                "us-east-1a is an availability zone name",
+            ),
+            (
+                r#"{"explicit": {"instance_type": "r7g.xlarge", "shard_count": 3,
+                    "replicas_per_shard": 2, "zones": ["use1-az1", "usw2"]}}"#,
+                "'usw2' is not an availability zone ID",
+            ),
+            (
+                r#"{"managed": {"capacity": {"min_gib": 32, "max_gib": 128},
+                    "replication": {"min_replicas_per_shard": 1, "max_replicas_per_shard": 2},
+                    "zones": [""]}}"#,
+                "'' is not an availability zone ID",
+            ),
+            (
+                r#"{"explicit": {"instance_type": "r7g.xlarge", "shard_count": 3,
+                    "replicas_per_shard": "1..3", "zones": ["use1-az1"]}}"#,
+                "Invalid pool spec",
+            ),
+        ] {
+            let path = write_spec(&dir, "pool.json", contents);
+            let err = read_provisioning_spec(&path).expect_err("spec should be rejected");
+            assert_reason_for(contents, &err, want);
+        }
+    }
+
+    #[test]
+    fn test_plan_spec_update_changes_only_differing_fields() {
+        let live = flex(32, 128, strings(["use1-az1"]));
+        let desired = flex(64, 128, strings(["use1-az1", "use1-az2"]));
+
+        let (changes, update) = plan_spec_update(&live, desired).expect("same mode should plan");
+        assert_eq!(
+            vec![
+                "capacity: 32..128 GiB -> 64..128 GiB",
+                "zones: use1-az1 -> use1-az1, use1-az2",
+            ],
+            changes
+        );
+        assert_eq!(
+            json!({"managed": {"capacity": {"min_gib": 64, "max_gib": 128}, "zones": ["use1-az1", "use1-az2"]}}),
+            serde_json::to_value(update).expect("should serialize")
+        );
+
+        let (changes, _) = plan_spec_update(&live, flex(32, 128, strings(["use1-az1"])))
+            .expect("same mode should plan");
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_plan_spec_update_rejects_mode_change() {
+        let live = CapacityPoolProvisioning::Cluster {
+            instance_type: "r7g.xlarge".to_string(),
+            shard_count: 3,
+            replicas_per_shard: 2,
+            zones: strings(["use1-az1"]),
+        };
+        let err = plan_spec_update(&live, flex(32, 128, strings(["use1-az1"])))
+            .expect_err("a spec can't switch modes");
+        assert_reason(&err, "can't change the mode");
+    }
 }
@@ -0,0 +1,356 @@
+use super::utils::{call_pool_get_api, CapacityPoolDiagnosticEntry, CapacityPoolResponse};
+use crate::commands::utils::MomentoHttpResponse::{DryRun, Parsed, Unparseable};
+use crate::{error::CliError, utils::console::console_data};
+
+use indicatif::ProgressBar;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const FIRST_POLL_DELAY: Duration = Duration::from_secs(2);
+const MAX_POLL_DELAY: Duration = Duration::from_secs(30);
+
+/// Pool statuses that won't turn into the one we're waiting for on their own.
+const FAILED_STATUSES: [&str; 2] = ["failed", "error"];
+/// Diagnostic states that mean provisioning has given up rather than retrying.
+const FAILED_DIAGNOSTIC_STATES: [&str; 2] = ["failed", "error"];
+
+/// What `pool wait` waits for: a status, or for the pool to be gone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoolTarget {
+    Status(String),
+    Deleted,
+}
+
+impl PoolTarget {
+    pub fn active() -> Self {
+        Self::Status("active".to_string())
+    }
+}
+
+impl From<String> for PoolTarget {
+    fn from(status: String) -> Self {
+        if status == "deleted" {
+            Self::Deleted
+        } else {
+            Self::Status(status)
+        }
+    }
+}
+
+impl fmt::Display for PoolTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Status(status) => write!(f, "{status}"),
+            Self::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
+/// What one poll found.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Progress {
+    Done,
+    Pending(String),
+    /// `--dry-run` printed the poll's request, so there's nothing to wait for.
+    DryRun,
+}
+
+/// Polls the pool, backing off between polls, until it reaches `target`, fails, or `timeout`
+/// passes.
+pub async fn wait_for_pool(
+    endpoint: String,
+    auth_token: String,
+    name: String,
+    target: PoolTarget,
+    timeout: Duration,
+) -> Result<(), CliError> {
+    let (name, target) = (&name, &target);
+    wait_until(
+        &format!("capacity pool {name} to be {target}"),
+        &format!("Capacity pool {name} is {target}"),
+        timeout,
+        || {
+            let pool = call_pool_get_api(endpoint.clone(), auth_token.clone(), name.clone());
+            async move {
+                match pool.await? {
+                    Parsed(pool) => check_progress(name, pool.as_ref(), target),
+                    Unparseable(response_text) => {
+                        Err(CliError::new(format!("Can't parse capacity pool {name}"))
+                            .with_details(response_text))
+                    }
+                    DryRun => Ok(Progress::DryRun),
+                }
+            }
+        },
+    )
+    .await
+}
+
+/// Runs `check` under a spinner, backing off between polls, until it's done, fails, or `timeout`
+/// passes. `goal` says what's being waited for, and `done` is printed once it's reached.
+pub(crate) async fn wait_until<F, Fut>(
+    goal: &str,
+    done: &str,
+    timeout: Duration,
+    mut check: F,
+) -> Result<(), CliError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Progress, CliError>>,
+{
+    let spinner = ProgressBar::new_spinner().with_message(format!("Waiting for {goal}"));
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    let deadline = Instant::now() + timeout;
+    let mut delay = FIRST_POLL_DELAY;
+    loop {
+        match check().await {
+            Ok(Progress::Done) => {
+                spinner.finish_and_clear();
+                console_data!("{done}");
+                return Ok(());
+            }
+            Ok(Progress::Pending(message)) => spinner.set_message(message),
+            Ok(Progress::DryRun) => {
+                spinner.finish_and_clear();
+                return Ok(());
+            }
+            Err(e) => {
+                spinner.finish_and_clear();
+                return Err(e);
+            }
+        }
+        if Instant::now() + delay > deadline {
+            spinner.finish_and_clear();
+            return Err(CliError::new(format!(
+                "Timed out after {timeout:?} waiting for {goal}"
+            )));
+        }
+        tokio::time::sleep(delay).await;
+        delay = next_delay(delay);
+    }
+}
+
+/// Backs off between polls, up to a limit.
+fn next_delay(delay: Duration) -> Duration {
+    (delay * 3 / 2).min(MAX_POLL_DELAY)
+}
+
+fn check_progress(
+    name: &str,
+    pool: Option<&CapacityPoolResponse>,
+    target: &PoolTarget,
+) -> Result<Progress, CliError> {
+    let Some(pool) = pool else {
+        return match target {
+            PoolTarget::Deleted => Ok(Progress::Done),
+            PoolTarget::Status(_) => {
+                Err(CliError::new(format!("Capacity pool {name} doesn't exist")))
+            }
+        };
+    };
+    if let Some(diagnostic) = failed_diagnostic(pool) {
+        return Err(CliError::new(format!(
+            "Capacity pool {name} reported a failure:\n{diagnostic}"
+        )));
+    }
+    match target {
+        PoolTarget::Status(status) if pool.status == *status && is_settled(pool) => {
+            Ok(Progress::Done)
+        }
+        _ if FAILED_STATUSES.contains(&pool.status.as_str()) => Err(CliError::new(format!(
+            "Capacity pool {name} is {}, so it won't become {target}",
+            pool.status
+        ))),
+        _ => Ok(Progress::Pending(progress_message(pool))),
+    }
+}
+
+fn failed_diagnostic(pool: &CapacityPoolResponse) -> Option<&CapacityPoolDiagnosticEntry> {
+    pool.diagnostics.as_ref()?.0.iter().find(|diagnostic| {
+        matches!(
+            diagnostic,
+            CapacityPoolDiagnosticEntry::Parsed { fields, .. }
+                if fields
+                    .get("state")
+                    .and_then(|state| state.as_str())
+                    .is_some_and(|state| FAILED_DIAGNOSTIC_STATES.contains(&state))
+        )
+    })
+}
+
+/// Whether a flex-mode pool's allocation has caught up with its target.
+fn is_settled(pool: &CapacityPoolResponse) -> bool {
+    let allocation = &pool.allocation;
+    let caught_up = |current: Option<u32>, target: Option<u32>| match (current, target) {
+        (Some(current), Some(target)) => current == target,
+        _ => true,
+    };
+    caught_up(
+        allocation.current_capacity_gib,
+        allocation.target_capacity_gib,
+    ) && caught_up(
+        allocation.current_replicas_per_shard,
+        allocation.target_replicas_per_shard,
+    )
+}
+
+fn progress_message(pool: &CapacityPoolResponse) -> String {
+    let allocation = &pool.allocation;
+    let describe = |current: Option<u32>, target: Option<u32>, unit: &str| match (current, target) {
+        (Some(current), Some(target)) if current != target => {
+            Some(format!("{current} -> {target} {unit}"))
+        }
+        (Some(current), _) => Some(format!("{current} {unit}")),
+        (None, Some(target)) => Some(format!("target {target} {unit}")),
+        (None, None) => None,
+    };
+    let details: Vec<String> = [
+        describe(
+            allocation.current_capacity_gib,
+            allocation.target_capacity_gib,
+            "GiB",
+        ),
+        describe(
+            allocation.current_replicas_per_shard,
+            allocation.target_replicas_per_shard,
+            "replicas per shard",
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if details.is_empty() {
+        format!("{}: {}", pool.name, pool.status)
+    } else {
+        format!("{}: {} ({})", pool.name, pool.status, details.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_pool(status: &str, extra: &str) -> CapacityPoolResponse {
+        serde_json::from_str(&format!(
+            r#"{{
+                "name": "pool",
+                "status": "{status}",
+                "provisioning": {{
+                    "managed": {{
+                        "capacity": {{"min_gib": 32, "max_gib": 128}},
+                        "replication": {{
+                            "min_replicas_per_shard": 1,
+                            "max_replicas_per_shard": 2
+                        }},
+                        "zones": ["use1-az1"]
+                    }}
+                }}{extra}
+            }}"#
+        ))
+        .expect("should parse a capacity pool")
+    }
+
+    #[test]
+    fn active_pool_is_done_once_its_allocation_settles() {
+        let scaling = parse_pool(
+            "active",
+            r#", "current_capacity_gib": 40, "target_capacity_gib": 128"#,
+        );
+        assert_eq!(
+            check_progress("pool", Some(&scaling), &PoolTarget::active()).expect("pending"),
+            Progress::Pending("pool: active (40 -> 128 GiB)".to_string())
+        );
+
+        let settled = parse_pool(
+            "active",
+            r#", "current_capacity_gib": 128, "target_capacity_gib": 128"#,
+        );
+        assert_eq!(
+            check_progress("pool", Some(&settled), &PoolTarget::active()).expect("done"),
+            Progress::Done
+        );
+    }
+
+    #[test]
+    fn missing_pool_is_only_done_when_waiting_for_deletion() {
+        assert_eq!(
+            check_progress("pool", None, &PoolTarget::from("deleted".to_string())).expect("done"),
+            Progress::Done
+        );
+        assert_eq!(
+            check_progress("pool", None, &PoolTarget::active())
+                .unwrap_err()
+                .msg,
+            "Capacity pool pool doesn't exist"
+        );
+        assert_eq!(
+            check_progress(
+                "pool",
+                Some(&parse_pool("deleting", "")),
+                &PoolTarget::Deleted
+            )
+            .expect("pending"),
+            Progress::Pending("pool: deleting".to_string())
+        );
+    }
+
+    #[test]
+    fn failed_diagnostics_and_statuses_fail_fast() {
+        let blocked = parse_pool(
+            "creating",
+            r#", "diagnostics": [{"insufficient_capacity": {"state": "failed"}}]"#,
+        );
+        let err = check_progress("pool", Some(&blocked), &PoolTarget::active()).unwrap_err();
+        assert!(
+            err.msg
+                .starts_with("Capacity pool pool reported a failure:\n- insufficient_capacity"),
+            "got: {}",
+            err.msg
+        );
+
+        let retrying = parse_pool(
+            "creating",
+            r#", "diagnostics": [{"insufficient_capacity": {"state": "active"}}]"#,
+        );
+        assert!(check_progress("pool", Some(&retrying), &PoolTarget::active()).is_ok());
+
+        assert_eq!(
+            check_progress(
+                "pool",
+                Some(&parse_pool("failed", "")),
+                &PoolTarget::active()
+            )
+            .unwrap_err()
+            .msg,
+            "Capacity pool pool is failed, so it won't become active"
+        );
+    }
+
+    #[tokio::test]
+    async fn waiting_stops_at_the_first_poll_that_isnt_pending() {
+        for (progress, ok) in [
+            (Ok(Progress::Done), true),
+            (Ok(Progress::DryRun), true),
+            (Err(CliError::new("lost")), false),
+        ] {
+            let mut progress = Some(progress);
+            let mut polls = 0;
+            let result = wait_until("a pool", "done", Duration::from_secs(60), || {
+                polls += 1;
+                let progress = progress.take().expect("should poll once");
+                async move { progress }
+            })
+            .await;
+            assert_eq!(result.is_ok(), ok);
+            assert_eq!(polls, 1);
+        }
+    }
+
+    #[test]
+    fn polls_back_off_to_a_limit() {
+        assert_eq!(next_delay(Duration::from_secs(2)), Duration::from_secs(3));
+        assert_eq!(next_delay(Duration::from_secs(25)), MAX_POLL_DELAY);
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::{BTreeMap, HashMap};
+use std::iter;
+use std::time::SystemTime;
+
+use aws_config::{BehaviorVersion, Region};
+use aws_credential_types::{provider::ProvideCredentials, Credentials};
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+use aws_smithy_xml::decode::{try_data, Document, XmlDecodeError};
+use momento_cli_opts::AvailabilityZone;
+
+use crate::commands::cloud_linter::utils::check_aws_credentials;
+use crate::commands::utils::shared_http_client;
+use crate::{error::CliError, utils::console::console_info};
+
+const EC2_API_VERSION: &str = "2016-11-15";
+
+/// Turns `--zones` into the zone IDs the API takes. Zone names only map to IDs within one AWS
+/// account, so they're looked up with the caller's AWS credentials, and only when asked to.
+pub async fn resolve_zones(
+    zones: Vec<AvailabilityZone>,
+    translate_zone_names: bool,
+    aws_profile: Option<String>,
+) -> Result<Vec<String>, CliError> {
+    let names = zone_names(&zones);
+    if names.is_empty() {
+        return translate(zones, &HashMap::new());
+    }
+    if !translate_zone_names {
+        return Err(CliError::new(format!(
+            "{} is an availability zone name; zones must be zone IDs, like usw2-az1. \
+             Pass --translate-zone-names to look up the IDs with your AWS credentials",
+            names[0]
+        )));
+    }
+
+    let ids = look_up_zone_ids(&names, aws_profile).await?;
+    let resolved = translate(zones, &ids)?;
+    console_info!("Using availability zone IDs {}", resolved.join(", "));
+    Ok(resolved)
+}
+
+/// Maps zone names to IDs with EC2's DescribeAvailabilityZones, region by region.
+async fn look_up_zone_ids(
+    names: &[String],
+    aws_profile: Option<String>,
+) -> Result<HashMap<String, String>, CliError> {
+    let mut ids = HashMap::new();
+    for (region, names) in names_by_region(names)? {
+        let mut loader =
+            aws_config::defaults(BehaviorVersion::latest()).region(Region::new(region.clone()));
+        if let Some(profile) = &aws_profile {
+            loader = loader.profile_name(profile);
+        }
+        let config = loader.load().await;
+        check_aws_credentials(&config).await?;
+        let credentials = config
+            .credentials_provider()
+            .expect("credentials were just checked")
+            .provide_credentials()
+            .await
+            .map_err(|e| CliError::new(format!("Could not load AWS credentials: {e}")))?;
+        let response =
+            describe_availability_zones(&ec2_endpoint(&region), &region, credentials, &names)
+                .await?;
+        ids.extend(zone_id_map(&response)?);
+    }
+    Ok(ids)
+}
+
+fn ec2_endpoint(region: &str) -> String {
+    let domain = if region.starts_with("cn-") {
+        "amazonaws.com.cn"
+    } else {
+        "amazonaws.com"
+    };
+    format!("https://ec2.{region}.{domain}/")
+}
+
+/// Calls DescribeAvailabilityZones through EC2's query API. It's a single signed GET, which
+/// doesn't justify building the whole EC2 SDK. Returns the response's XML.
+async fn describe_availability_zones(
+    endpoint: &str,
+    region: &str,
+    credentials: Credentials,
+    names: &[String],
+) -> Result<String, CliError> {
+    let mut query = form_urlencoded::Serializer::new(String::new());
+    query
+        .append_pair("Action", "DescribeAvailabilityZones")
+        .append_pair("Version", EC2_API_VERSION)
+        .append_pair("AllAvailabilityZones", "true");
+    for (index, name) in names.iter().enumerate() {
+        query.append_pair(&format!("ZoneName.{}", index + 1), name);
+    }
+    let url = format!("{endpoint}?{}", query.finish());
+
+    let identity = credentials.into();
+    let params = v4::SigningParams::builder()
+        .identity(&identity)
+        .region(region)
+        .name("ec2")
+        .time(SystemTime::now())
+        .settings(SigningSettings::default())
+        .build()
+        .map_err(|e| CliError::new(format!("Could not sign the EC2 request: {e}")))?
+        .into();
+    let signed = SignableRequest::new("GET", url.as_str(), iter::empty(), SignableBody::Bytes(&[]))
+        .and_then(|request| sign(request, &params))
+        .map_err(|e| CliError::new(format!("Could not sign the EC2 request: {e}")))?;
+    let (instructions, _signature) = signed.into_parts();
+
+    let mut request = shared_http_client()?.get(&url);
+    for (name, value) in instructions.headers() {
+        request = request.header(name, value);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        let reason = ec2_error_message(&body).unwrap_or_else(|| status.to_string());
+        return Err(CliError::new(format!(
+            "Could not look up availability zones in {region}: {reason}"
+        ))
+        .with_details(body));
+    }
+    Ok(body)
+}
+
+fn zone_names(zones: &[AvailabilityZone]) -> Vec<String> {
+    zones
+        .iter()
+        .filter_map(|zone| match zone {
+            AvailabilityZone::Name(name) => Some(name.clone()),
+            AvailabilityZone::Id(_) => None,
+        })
+        .collect()
+}
+
+/// The region a zone name is in: `us-west-2` for `us-west-2a`, and for local zones like
+/// `us-west-2-lax-1a` too.
+fn zone_region(name: &str) -> Option<String> {
+    let mut region = Vec::new();
+    for part in name.split('-') {
+        if part.starts_with(|c: char| c.is_ascii_digit()) {
+            region.push(part.trim_end_matches(|c: char| c.is_ascii_lowercase()));
+            return Some(region.join("-"));
+        }
+        region.push(part);
+    }
+    None
+}
+
+fn names_by_region(names: &[String]) -> Result<BTreeMap<String, Vec<String>>, CliError> {
+    let mut by_region: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for name in names {
+        let region = zone_region(name)
+            .ok_or_else(|| CliError::new(format!("Could not tell which region {name} is in")))?;
+        by_region.entry(region).or_default().push(name.clone());
+    }
+    Ok(by_region)
+}
+
+/// Maps zone names to IDs from a DescribeAvailabilityZones response, which lists the zones as
+/// `<availabilityZoneInfo><item><zoneName>…</zoneName><zoneId>…</zoneId></item>…`.
+fn zone_id_map(response: &str) -> Result<HashMap<String, String>, CliError> {
+    let unreadable =
+        |e: XmlDecodeError| CliError::new(format!("Could not read the EC2 response: {e}"));
+    let mut document = Document::new(response);
+    let mut root = document.root_element().map_err(unreadable)?;
+    let mut ids = HashMap::new();
+    while let Some(mut element) = root.next_tag() {
+        if !element.start_el().matches("availabilityZoneInfo") {
+            continue;
+        }
+        while let Some(mut item) = element.next_tag() {
+            let (mut name, mut id) = (None, None);
+            while let Some(mut field) = item.next_tag() {
+                if field.start_el().matches("zoneName") {
+                    name = Some(try_data(&mut field).map_err(unreadable)?.into_owned());
+                } else if field.start_el().matches("zoneId") {
+                    id = Some(try_data(&mut field).map_err(unreadable)?.into_owned());
+                }
+            }
+            if let (Some(name), Some(id)) = (name, id) {
+                ids.insert(name, id);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// The message from an EC2 error response:
+/// `<Response><Errors><Error><Code>…</Code><Message>…</Message></Error></Errors>…`.
+fn ec2_error_message(response: &str) -> Option<String> {
+    let mut document = Document::new(response);
+    let mut root = document.root_element().ok()?;
+    while let Some(mut errors) = root.next_tag() {
+        while let Some(mut error) = errors.next_tag() {
+            while let Some(mut field) = error.next_tag() {
+                if field.start_el().matches("Message") {
+                    return try_data(&mut field)
+                        .ok()
+                        .map(|message| message.into_owned());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Swaps each zone name for its ID, keeping the zones in the order they were given.
+fn translate(
+    zones: Vec<AvailabilityZone>,
+    ids: &HashMap<String, String>,
+) -> Result<Vec<String>, CliError> {
+    zones
+        .into_iter()
+        .map(|zone| match zone {
+            AvailabilityZone::Id(id) => Ok(id),
+            AvailabilityZone::Name(name) => ids.get(&name).cloned().ok_or_else(|| {
+                CliError::new(format!(
+                    "AWS doesn't know availability zone {name} in this account"
+                ))
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A DescribeAvailabilityZones response, as EC2 sends it, trimmed to the fields that matter.
+    const DESCRIBED_ZONES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<DescribeAvailabilityZonesResponse xmlns="http://ec2.amazonaws.com/doc/2016-11-15/">
+    <requestId>4e2d8d3b-0d8a-4a8b-9a52-3f1c2b0e6f11</requestId>
+    <availabilityZoneInfo>
+        <item>
+            <zoneName>us-west-2a</zoneName>
+            <zoneState>available</zoneState>
+            <regionName>us-west-2</regionName>
+            <messageSet/>
+            <zoneId>usw2-az2</zoneId>
+            <zoneType>availability-zone</zoneType>
+        </item>
+        <item>
+            <zoneName>us-west-2b</zoneName>
+            <zoneState>available</zoneState>
+            <regionName>us-west-2</regionName>
+            <messageSet/>
+            <zoneId>usw2-az1</zoneId>
+            <zoneType>availability-zone</zoneType>
+        </item>
+    </availabilityZoneInfo>
+</DescribeAvailabilityZonesResponse>"#;
+
+    const AUTH_FAILURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Response><Errors><Error><Code>AuthFailure</Code><Message>AWS was not able to validate the provided access credentials</Message></Error></Errors><RequestID>59dbff89-35bd-4eac-99ed-be587EXAMPLE</RequestID></Response>"#;
+
+    /// Answers one request with `body`, and reports the request it received.
+    async fn serve_ec2(status_line: &str, body: &str) -> (String, tokio::task::JoinHandle<String>) {
+        let response = format!(
+            "HTTP/1.1 {status_line}\r\ncontent-type: text/xml\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("should bind a local port");
+        let port = listener.local_addr().expect("should have address").port();
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.expect("should accept");
+            let mut request = vec![];
+            let mut buffer = [0; 4096];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = socket.read(&mut buffer).await.expect("should read request");
+                request.extend_from_slice(&buffer[..read]);
+            }
+            socket
+                .write_all(response.as_bytes())
+                .await
+                .expect("should write response");
+            String::from_utf8_lossy(&request).into_owned()
+        });
+        (format!("http://127.0.0.1:{port}/"), handle)
+    }
+
+    fn test_credentials() -> Credentials {
+        Credentials::new("AKIDEXAMPLE", "secret", None, None, "test")
+    }
+
+    #[test]
+    fn names_are_grouped_by_region() {
+        assert_eq!(zone_region("us-west-2a"), Some("us-west-2".to_string()));
+        assert_eq!(
+            zone_region("us-gov-west-1b"),
+            Some("us-gov-west-1".to_string())
+        );
+        assert_eq!(
+            zone_region("us-west-2-lax-1a"),
+            Some("us-west-2".to_string())
+        );
+
+        let by_region = names_by_region(&[
+            "us-west-2a".to_string(),
+            "us-east-1c".to_string(),
+            "us-west-2b".to_string(),
+        ])
+        .expect("regions");
+        assert_eq!(
+            by_region.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("us-east-1".to_string(), vec!["us-east-1c".to_string()]),
+                (
+                    "us-west-2".to_string(),
+                    vec!["us-west-2a".to_string(), "us-west-2b".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn names_translate_to_ids_in_order() {
+        let ids = zone_id_map(DESCRIBED_ZONES).expect("parsed");
+        let zones = vec![
+            AvailabilityZone::Name("us-west-2b".to_string()),
+            AvailabilityZone::Id("usw2-az3".to_string()),
+            AvailabilityZone::Name("us-west-2a".to_string()),
+        ];
+        assert_eq!(
+            translate(zones, &ids).expect("translated"),
+            vec!["usw2-az1", "usw2-az3", "usw2-az2"]
+        );
+
+        let unknown = vec![AvailabilityZone::Name("us-west-2d".to_string())];
+        assert_eq!(
+            translate(unknown, &ids).unwrap_err().msg,
+            "AWS doesn't know availability zone us-west-2d in this account"
+        );
+    }
+
+    #[tokio::test]
+    async fn names_need_translate_zone_names() {
+        let zones = vec![
+            AvailabilityZone::Id("usw2-az1".to_string()),
+            AvailabilityZone::Name("us-west-2a".to_string()),
+        ];
+        let err = resolve_zones(zones, false, None).await.unwrap_err();
+        assert!(
+            err.msg
+                .starts_with("us-west-2a is an availability zone name"),
+            "got: {}",
+            err.msg
+        );
+
+        let ids = vec![AvailabilityZone::Id("usw2-az1".to_string())];
+        assert_eq!(
+            resolve_zones(ids, false, None).await.expect("ids"),
+            vec!["usw2-az1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn zones_are_described_with_a_signed_request() {
+        let (endpoint, server) = serve_ec2("200 OK", DESCRIBED_ZONES).await;
+        let names = vec!["us-west-2b".to_string(), "us-west-2a".to_string()];
+        let response =
+            describe_availability_zones(&endpoint, "us-west-2", test_credentials(), &names)
+                .await
+                .expect("described");
+        assert_eq!(
+            zone_id_map(&response).expect("parsed"),
+            HashMap::from([
+                ("us-west-2a".to_string(), "usw2-az2".to_string()),
+                ("us-west-2b".to_string(), "usw2-az1".to_string()),
+            ])
+        );
+
+        let request = server.await.expect("server should finish").to_lowercase();
+        assert!(
+            request.starts_with(
+                "get /?action=describeavailabilityzones&version=2016-11-15\
+                 &allavailabilityzones=true&zonename.1=us-west-2b&zonename.2=us-west-2a http/1.1"
+            ),
+            "got: {request}"
+        );
+        assert!(
+            request.contains("authorization: aws4-hmac-sha256 credential=akidexample/"),
+            "got: {request}"
+        );
+        assert!(
+            request.contains("/us-west-2/ec2/aws4_request"),
+            "got: {request}"
+        );
+        assert!(request.contains("x-amz-date: "), "got: {request}");
+    }
+
+    #[tokio::test]
+    async fn ec2_errors_are_reported() {
+        let (endpoint, _server) = serve_ec2("401 Unauthorized", AUTH_FAILURE).await;
+        let names = vec!["us-west-2a".to_string()];
+        let err = describe_availability_zones(&endpoint, "us-west-2", test_credentials(), &names)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.msg,
+            "Could not look up availability zones in us-west-2: \
+             AWS was not able to validate the provided access credentials"
+        );
+    }
+
+    #[test]
+    fn endpoints_follow_the_region() {
+        assert_eq!(
+            ec2_endpoint("us-west-2"),
+            "https://ec2.us-west-2.amazonaws.com/"
+        );
+        assert_eq!(
+            ec2_endpoint("cn-north-1"),
+            "https://ec2.cn-north-1.amazonaws.com.cn/"
+        );
+    }
+}
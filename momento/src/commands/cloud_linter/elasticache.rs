@@ -9,7 +9,7 @@ use governor::DefaultDirectRateLimiter;
 use indicatif::{ProgressBar, ProgressStyle};
 use log::debug;
 use phf::{phf_map, Map};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
 
 use crate::commands::cloud_linter::metrics::{Metric, MetricTarget, ResourceWithMetrics};
@@ -56,17 +56,17 @@ pub(crate) const CACHE_METRICS: Map<&'static str, &'static [&'static str]> = phf
         ],
 };
 
-#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub(crate) struct ElastiCacheMetadata {
     #[serde(rename = "clusterId")]
-    cluster_id: String,
-    engine: String,
+    pub(crate) cluster_id: String,
+    pub(crate) engine: String,
     #[serde(rename = "cacheNodeType")]
-    cache_node_type: String,
+    pub(crate) cache_node_type: String,
     #[serde(rename = "preferredAz")]
-    preferred_az: String,
+    pub(crate) preferred_az: String,
     #[serde(rename = "clusterModeEnabled")]
-    cluster_mode_enabled: bool,
+    pub(crate) cluster_mode_enabled: bool,
 }
 
 impl ResourceWithMetrics for ElastiCacheResource {
@@ -3,7 +3,7 @@ mod dynamodb;
 mod elasticache;
 pub mod linter_cli;
 mod metrics;
-mod resource;
+pub(crate) mod resource;
 mod s3;
 mod serverless_elasticache;
-mod utils;
+pub(crate) mod utils;
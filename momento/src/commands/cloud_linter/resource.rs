@@ -1,5 +1,5 @@
 use crate::commands::cloud_linter::api_gateway::ApiGatewayMetadata;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::commands::cloud_linter::dynamodb::DynamoDbMetadata;
 use crate::commands::cloud_linter::elasticache::ElastiCacheMetadata;
@@ -17,7 +17,7 @@ pub(crate) enum Resource {
     S3(S3Resource),
 }
 
-#[derive(Debug, Serialize, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
 pub(crate) enum ResourceType {
     #[serde(rename = "AWS::ApiGateway::API")]
     ApiGateway,
@@ -49,7 +49,7 @@ pub(crate) struct DynamoDbResource {
     pub(crate) metadata: DynamoDbMetadata,
 }
 
-#[derive(Serialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub(crate) struct ElastiCacheResource {
     #[serde(rename = "type")]
     pub(crate) resource_type: ResourceType,
@@ -226,6 +226,7 @@ async fn prompt_user_for_config(quick: bool, profile_name: &str) -> Result<Confi
     Ok(Config {
         cache: cache_name_to_use,
         ttl,
+        ..current_config
     })
 }
 
@@ -1,10 +1,30 @@
+use super::migrate::{self, MigrateOptions};
+use super::resp::{connect_tls, split_command_line, RespEndpoint, RespValue};
 use super::utils::{call_database_api, call_database_delete_api, call_database_list_api};
-use crate::commands::database::utils::{print_valkey_cli_sample, ListDatabasesResponse};
-use crate::commands::utils::MomentoHttpResponse::{Parsed, Unparseable};
-use crate::{error::CliError, utils::console::console_data};
+use crate::commands::capacity_pool::utils::call_pool_api;
+use crate::commands::capacity_pool::wait::{wait_until, Progress};
+use crate::commands::database::utils::{
+    check_target_pool, print_valkey_cli_sample, DatabaseCredentials, ListDatabasesResponse,
+};
+use crate::commands::utils::MomentoHttpResponse::{DryRun, Parsed, Unparseable};
+use crate::{
+    error::CliError,
+    utils::{
+        client::{get_auth_client, interact_with_momento},
+        confirm::{ResourceKind, Safeguards},
+        console::{console_data, console_info},
+        dry_run::skip_sdk_request,
+    },
+};
 
 use http::Method;
+use momento::auth::{ExpiresIn, PermissionScopes};
+use momento::CredentialProvider;
+use momento_cli_opts::{CredentialsFormat, CredentialsToken};
 use serde_json;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, BufReader};
 
 pub async fn create_database(
     api_endpoint: String,
@@ -37,6 +57,7 @@ pub async fn create_database(
                 console_data!("\n\n{response_text}");
             }
         }
+        DryRun => return Ok(()),
     };
     print_valkey_cli_sample(valkey_hostname, &database_name);
     Ok(())
@@ -65,19 +86,127 @@ pub async fn describe_database(
                 }
                 "<DATABASE NAME>".to_string()
             }
+            DryRun => return Ok(()),
         };
     print_valkey_cli_sample(valkey_hostname, &database_name);
     Ok(())
 }
 
+/// Moves a database to another capacity pool, once that pool is confirmed to be active.
+pub async fn update_database(
+    api_endpoint: String,
+    auth_token: String,
+    name: String,
+    pool_name: String,
+    wait: Option<Duration>,
+) -> Result<(), CliError> {
+    if let Parsed(database) = call_database_api(
+        Method::GET,
+        api_endpoint.clone(),
+        auth_token.clone(),
+        name.clone(),
+        None,
+    )
+    .await?
+    {
+        if database.pool_name == pool_name {
+            console_data!("Database {name} is already in capacity pool {pool_name}");
+            return Ok(());
+        }
+    }
+    match call_pool_api(
+        Method::GET,
+        api_endpoint.clone(),
+        auth_token.clone(),
+        pool_name.clone(),
+        None,
+    )
+    .await
+    .map_err(|e| {
+        CliError::new(format!(
+            "Could not find capacity pool {pool_name}: {}",
+            e.msg
+        ))
+    })? {
+        Parsed(pool) => check_target_pool(&pool_name, &pool.status)?,
+        Unparseable(response_text) => {
+            return Err(CliError::new(format!(
+                "Could not read capacity pool {pool_name}'s status: {response_text}"
+            )))
+        }
+        // A dry run has no status to check, so go on to show the update.
+        DryRun => {}
+    }
+
+    let moved = call_database_api(
+        Method::PATCH,
+        api_endpoint.clone(),
+        auth_token.clone(),
+        name.clone(),
+        Some(serde_json::json!({
+            "pool_name": pool_name
+        })),
+    )
+    .await?;
+    if !matches!(moved, DryRun) {
+        console_data!("Moving database {name} to capacity pool {pool_name}");
+    }
+    if let Some(timeout) = wait {
+        wait_for_database_pool(api_endpoint, auth_token, name, pool_name, timeout).await?;
+    }
+    Ok(())
+}
+
+/// Polls the database until `describe` shows it in `pool_name`, or `timeout` passes.
+async fn wait_for_database_pool(
+    api_endpoint: String,
+    auth_token: String,
+    name: String,
+    pool_name: String,
+    timeout: Duration,
+) -> Result<(), CliError> {
+    let goal = format!("database {name} to move to capacity pool {pool_name}");
+    let (name, pool_name, goal_ref) = (&name, &pool_name, &goal);
+    wait_until(
+        &goal,
+        &format!("Database {name} is in capacity pool {pool_name}"),
+        timeout,
+        || {
+            let database = call_database_api(
+                Method::GET,
+                api_endpoint.clone(),
+                auth_token.clone(),
+                name.clone(),
+                None,
+            );
+            async move {
+                Ok(match database.await? {
+                    Parsed(database) if database.pool_name == *pool_name => Progress::Done,
+                    Parsed(database) => Progress::Pending(format!(
+                        "Database {name} is still in capacity pool {}",
+                        database.pool_name
+                    )),
+                    Unparseable(_) => Progress::Pending(format!("Waiting for {goal_ref}")),
+                    DryRun => Progress::DryRun,
+                })
+            }
+        },
+    )
+    .await
+}
+
 pub async fn delete_database(
     endpoint: String,
     auth_token: String,
     database_name: String,
+    safeguards: Safeguards,
 ) -> Result<(), CliError> {
-    let response_text =
-        call_database_delete_api(endpoint, auth_token, database_name.clone()).await?;
-    console_data!("Deleting database {database_name}! {response_text}");
+    safeguards.check(ResourceKind::Database, "delete", &database_name)?;
+    if let Some(response_text) =
+        call_database_delete_api(endpoint, auth_token, database_name.clone()).await?
+    {
+        console_data!("Deleting database {database_name}! {response_text}");
+    }
     Ok(())
 }
 
@@ -114,9 +243,248 @@ pub async fn list_databases(
             console_data!("Listing databases:\n\n{response_text}");
             Some("<DATABASE NAME>".to_string())
         }
+        DryRun => None,
     };
     if let Some(database_name) = database_name {
         print_valkey_cli_sample(valkey_hostname, &database_name);
     }
     Ok(())
 }
+
+/// Prints how to connect to a database, after checking that it exists.
+#[allow(clippy::too_many_arguments)]
+pub async fn database_credentials(
+    api_endpoint: String,
+    valkey_hostname: String,
+    credential_provider: CredentialProvider,
+    name: String,
+    port: u16,
+    format: CredentialsFormat,
+    token: CredentialsToken,
+    token_ttl: Duration,
+) -> Result<(), CliError> {
+    let auth_token = credential_provider.auth_token().to_string();
+    let user = match call_database_api(
+        Method::GET,
+        api_endpoint,
+        auth_token.clone(),
+        name.clone(),
+        None,
+    )
+    .await?
+    {
+        Parsed(database) => database.name,
+        Unparseable(_) | DryRun => name,
+    };
+    let password = match token {
+        CredentialsToken::ApiKey => auth_token,
+        CredentialsToken::Disposable => {
+            match vend_disposable_token(credential_provider, &user, token_ttl).await? {
+                Some(token) => token,
+                None => return Ok(()),
+            }
+        }
+    };
+    let credentials = DatabaseCredentials {
+        host: valkey_hostname,
+        port,
+        user,
+        password,
+    };
+    console_data!("{}", credentials.format(format));
+    Ok(())
+}
+
+/// Vends a disposable token that can only read and write `database_name`, or `None` on a dry run.
+async fn vend_disposable_token(
+    credential_provider: CredentialProvider,
+    database_name: &str,
+    ttl: Duration,
+) -> Result<Option<String>, CliError> {
+    if skip_sdk_request(
+        "GenerateDisposableToken",
+        &[
+            ("scope", format!("read-write on {database_name}")),
+            ("expires_in", format!("{}s", ttl.as_secs())),
+        ],
+    ) {
+        return Ok(None);
+    }
+    let client = get_auth_client(credential_provider).await?;
+    let response = interact_with_momento(
+        "generating disposable token...",
+        client.generate_disposable_token(
+            PermissionScopes::cache_read_write(database_name).into(),
+            ExpiresIn::seconds(ttl.as_secs()),
+        ),
+    )
+    .await?;
+    Ok(Some(response.auth_token()))
+}
+
+/// Where and how to reach a database over RESP.
+pub struct ConnectionOptions {
+    pub valkey_hostname: String,
+    pub port: u16,
+    pub database_name: String,
+    pub auth_token: String,
+    pub ca_bundle_paths: Vec<String>,
+}
+
+impl ConnectionOptions {
+    fn address(&self) -> String {
+        format!("{}:{}", self.valkey_hostname, self.port)
+    }
+
+    fn skip(&self, operation: &str, command: &str) -> bool {
+        skip_sdk_request(
+            operation,
+            &[
+                ("address", self.address()),
+                ("user", self.database_name.clone()),
+                ("password", self.auth_token.clone()),
+                ("command", command.to_string()),
+            ],
+        )
+    }
+}
+
+/// Runs `exec` against the database, or reads commands from stdin with a `valkey-cli`-style
+/// prompt until `quit` or end of input.
+pub async fn connect_database(
+    options: ConnectionOptions,
+    exec: Option<String>,
+) -> Result<(), CliError> {
+    if options.skip("RespConnect", exec.as_deref().unwrap_or("<interactive>")) {
+        return Ok(());
+    }
+    let exec = exec.map(|line| split_command_line(&line)).transpose()?;
+    let mut connection = connect_tls(
+        &options.valkey_hostname,
+        options.port,
+        &options.ca_bundle_paths,
+    )
+    .await?;
+    connection
+        .authenticate(&options.database_name, &options.auth_token)
+        .await?;
+
+    if let Some(args) = exec {
+        return match connection.command(&args).await? {
+            RespValue::Error(message) => Err(CliError::new(message)),
+            reply => {
+                console_data!("{}", reply.display());
+                Ok(())
+            }
+        };
+    }
+
+    let prompt = std::io::stdin()
+        .is_terminal()
+        .then(|| format!("{}> ", options.address()));
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        if let Some(prompt) = &prompt {
+            print!("{prompt}");
+            std::io::stdout().flush().ok();
+        }
+        let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| CliError::new(format!("Could not read a command: {e}")))?
+        else {
+            return Ok(());
+        };
+        let args = match split_command_line(&line) {
+            Ok(args) => args,
+            Err(e) => {
+                console_info!("{}", e.msg);
+                continue;
+            }
+        };
+        match args
+            .first()
+            .map(|command| command.to_lowercase())
+            .as_deref()
+        {
+            None => continue,
+            Some("quit" | "exit") => return Ok(()),
+            Some(_) => console_data!("{}", connection.command(&args).await?.display()),
+        }
+    }
+}
+
+/// Connects, authenticates and sends `PING`, reporting how long each step took.
+pub async fn ping_database(options: ConnectionOptions) -> Result<(), CliError> {
+    if options.skip("RespPing", "PING") {
+        return Ok(());
+    }
+    let started = Instant::now();
+    let mut connection = connect_tls(
+        &options.valkey_hostname,
+        options.port,
+        &options.ca_bundle_paths,
+    )
+    .await?;
+    connection
+        .authenticate(&options.database_name, &options.auth_token)
+        .await?;
+    let connected = started.elapsed();
+
+    let started = Instant::now();
+    let reply = connection.command(&["PING"]).await?;
+    let ping = started.elapsed();
+    match reply {
+        RespValue::Simple(pong) if pong == "PONG" => {
+            console_data!(
+                "PONG from {} in {:.1} ms (connecting and authenticating took {:.1} ms)",
+                options.address(),
+                ping.as_secs_f64() * 1000.0,
+                connected.as_secs_f64() * 1000.0
+            );
+            Ok(())
+        }
+        other => Err(CliError::new(format!(
+            "{} answered PING with {}",
+            options.address(),
+            other.display()
+        ))),
+    }
+}
+
+/// Copies every key from the server at `source` into the database. `checkpoint` defaults to a
+/// file named after the database in the working directory.
+pub async fn migrate_database(
+    options: ConnectionOptions,
+    source: String,
+    concurrency: u16,
+    checkpoint: Option<String>,
+    restart: bool,
+    recreate: bool,
+    verify: bool,
+) -> Result<(), CliError> {
+    let source = RespEndpoint::from_url(&source, options.ca_bundle_paths.clone())?;
+    let checkpoint_path = checkpoint
+        .unwrap_or_else(|| format!("momento-migrate-{}.checkpoint", options.database_name));
+    let destination = RespEndpoint {
+        host: options.valkey_hostname,
+        port: options.port,
+        tls: true,
+        user: Some(options.database_name),
+        password: Some(options.auth_token),
+        database: None,
+        ca_bundle_paths: options.ca_bundle_paths,
+    };
+    migrate::migrate_database(
+        source,
+        destination,
+        MigrateOptions {
+            concurrency: concurrency.into(),
+            checkpoint_path,
+            restart,
+            recreate,
+            verify,
+        },
+    )
+    .await
+}
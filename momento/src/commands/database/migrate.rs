@@ -0,0 +1,1094 @@
+use super::resp::{AnyRespConnection, RespEndpoint, RespValue};
+use crate::{
+    error::CliError,
+    utils::{console::console_data, dry_run::skip_sdk_request},
+};
+
+use futures::future::try_join_all;
+use indicatif::ProgressBar;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How many keys each SCAN asks for. The checkpoint advances a batch at a time.
+const SCAN_COUNT: usize = 100;
+
+/// How many arguments to send in one command when re-creating a large collection.
+const MAX_ARGS_PER_COMMAND: usize = 500;
+
+/// Mismatches listed in full before the rest are only counted.
+const MAX_REPORTED_MISMATCHES: usize = 10;
+
+pub struct MigrateOptions {
+    pub concurrency: usize,
+    pub checkpoint_path: String,
+    pub restart: bool,
+    pub recreate: bool,
+    pub verify: bool,
+}
+
+/// How far a migration got, so an interrupted one can pick up after its last finished batch.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Checkpoint {
+    source: String,
+    destination: String,
+    /// The `host:port` of the source node being scanned; a cluster's primaries are scanned one
+    /// after another.
+    node: Option<String>,
+    cursor: String,
+    copied: u64,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct CopyCounts {
+    restored: u64,
+    recreated: u64,
+    vanished: u64,
+}
+
+impl CopyCounts {
+    fn add(&mut self, other: CopyCounts) {
+        self.restored += other.restored;
+        self.recreated += other.recreated;
+        self.vanished += other.vanished;
+    }
+
+    fn copied(&self) -> u64 {
+        self.restored + self.recreated
+    }
+}
+
+enum Outcome {
+    Restored,
+    Recreated,
+    /// The key expired or was deleted between the SCAN that found it and the copy.
+    Vanished,
+}
+
+/// A key's value, read with the commands for its type. Hash fields and set members are sorted,
+/// so values compare equal however the server ordered them.
+#[derive(Debug, PartialEq)]
+enum KeyValue {
+    String(Vec<u8>),
+    Hash(Vec<Vec<u8>>),
+    List(Vec<Vec<u8>>),
+    Set(Vec<Vec<u8>>),
+    /// Members and scores, alternating, in score order.
+    SortedSet(Vec<Vec<u8>>),
+    /// A type, like a stream, that only DUMP and RESTORE can copy.
+    Other(String),
+}
+
+impl KeyValue {
+    /// The commands that write this value to `key` from scratch.
+    fn write_commands(&self, key: &[u8]) -> Result<Vec<Vec<Vec<u8>>>, CliError> {
+        let command = |name: &str, args: &[Vec<u8>]| {
+            let mut command = vec![name.as_bytes().to_vec(), key.to_vec()];
+            command.extend_from_slice(args);
+            command
+        };
+        let mut commands = vec![command("DEL", &[])];
+        let (name, args, per_item) = match self {
+            KeyValue::String(value) => {
+                commands.push(command("SET", std::slice::from_ref(value)));
+                return Ok(commands);
+            }
+            KeyValue::Hash(pairs) => ("HSET", pairs.clone(), 2),
+            KeyValue::List(items) => ("RPUSH", items.clone(), 1),
+            KeyValue::Set(members) => ("SADD", members.clone(), 1),
+            KeyValue::SortedSet(pairs) => (
+                "ZADD",
+                pairs
+                    .chunks(2)
+                    .flat_map(|pair| pair.iter().rev().cloned())
+                    .collect(),
+                2,
+            ),
+            KeyValue::Other(value_type) => {
+                return Err(CliError::new(format!(
+                    "Can't copy {}: it's a {value_type}, which can only be copied with DUMP and RESTORE",
+                    String::from_utf8_lossy(key)
+                )))
+            }
+        };
+        let chunk_size = MAX_ARGS_PER_COMMAND - MAX_ARGS_PER_COMMAND % per_item;
+        commands.extend(args.chunks(chunk_size).map(|chunk| command(name, chunk)));
+        Ok(commands)
+    }
+}
+
+fn bulk_items(reply: RespValue, command: &str, key: &[u8]) -> Result<Vec<Vec<u8>>, CliError> {
+    match reply {
+        RespValue::Array(items) => items
+            .into_iter()
+            .map(|item| match item {
+                RespValue::Bulk(bytes) => Ok(bytes),
+                other => Err(unexpected_reply(command, key, &other)),
+            })
+            .collect(),
+        other => Err(unexpected_reply(command, key, &other)),
+    }
+}
+
+fn sorted_pairs(items: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    let mut pairs: Vec<&[Vec<u8>]> = items.chunks(2).collect();
+    pairs.sort();
+    pairs.concat()
+}
+
+fn unexpected_reply(command: &str, key: &[u8], reply: &RespValue) -> CliError {
+    CliError::new(format!(
+        "Unexpected reply to {command} {}: {}",
+        String::from_utf8_lossy(key),
+        reply.display()
+    ))
+}
+
+async fn read_value(
+    connection: &mut AnyRespConnection,
+    key: &[u8],
+) -> Result<Option<KeyValue>, CliError> {
+    let key_command = |name: &str| vec![name.as_bytes().to_vec(), key.to_vec()];
+    let range = |name: &str, extra: &[&str]| {
+        let mut command = key_command(name);
+        command.extend(
+            ["0", "-1"]
+                .iter()
+                .chain(extra)
+                .map(|arg| arg.as_bytes().to_vec()),
+        );
+        command
+    };
+    let value_type = match connection.command(&key_command("TYPE")).await? {
+        RespValue::Simple(value_type) => value_type,
+        other => return Err(unexpected_reply("TYPE", key, &other)),
+    };
+    let value = match value_type.as_str() {
+        "none" => return Ok(None),
+        "string" => match connection.command(&key_command("GET")).await? {
+            RespValue::Bulk(value) => KeyValue::String(value),
+            RespValue::Null => return Ok(None),
+            other => return Err(unexpected_reply("GET", key, &other)),
+        },
+        "hash" => KeyValue::Hash(sorted_pairs(bulk_items(
+            connection.command(&key_command("HGETALL")).await?,
+            "HGETALL",
+            key,
+        )?)),
+        "list" => KeyValue::List(bulk_items(
+            connection.command(&range("LRANGE", &[])).await?,
+            "LRANGE",
+            key,
+        )?),
+        "set" => {
+            let mut members = bulk_items(
+                connection.command(&key_command("SMEMBERS")).await?,
+                "SMEMBERS",
+                key,
+            )?;
+            members.sort();
+            KeyValue::Set(members)
+        }
+        "zset" => KeyValue::SortedSet(bulk_items(
+            connection
+                .command(&range("ZRANGE", &["WITHSCORES"]))
+                .await?,
+            "ZRANGE",
+            key,
+        )?),
+        other => KeyValue::Other(other.to_string()),
+    };
+    Ok(Some(value))
+}
+
+/// A source and a destination connection, copying or checking keys one at a time.
+struct Worker {
+    source: AnyRespConnection,
+    destination: AnyRespConnection,
+    /// Set once DUMP or RESTORE is turned down, so the rest of the keys are re-created instead.
+    recreate: bool,
+}
+
+impl Worker {
+    async fn copy_keys(&mut self, keys: Vec<Vec<u8>>) -> Result<CopyCounts, CliError> {
+        let mut counts = CopyCounts::default();
+        for key in keys {
+            match self.copy_key(&key).await? {
+                Outcome::Restored => counts.restored += 1,
+                Outcome::Recreated => counts.recreated += 1,
+                Outcome::Vanished => counts.vanished += 1,
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn copy_key(&mut self, key: &[u8]) -> Result<Outcome, CliError> {
+        let ttl = match self.source.command(&[b"PTTL".as_slice(), key]).await? {
+            RespValue::Integer(-2) => return Ok(Outcome::Vanished),
+            RespValue::Integer(ttl) => ttl.max(0),
+            other => return Err(unexpected_reply("PTTL", key, &other)),
+        };
+        if !self.recreate {
+            match self.source.command(&[b"DUMP".as_slice(), key]).await? {
+                RespValue::Bulk(payload) => {
+                    let restore = [
+                        b"RESTORE".to_vec(),
+                        key.to_vec(),
+                        ttl.to_string().into_bytes(),
+                        payload,
+                        b"REPLACE".to_vec(),
+                    ];
+                    match self.destination.command(&restore).await? {
+                        RespValue::Error(message) => {
+                            debug!("RESTORE failed ({message}); re-creating keys instead");
+                            self.recreate = true;
+                        }
+                        _ => return Ok(Outcome::Restored),
+                    }
+                }
+                RespValue::Null => return Ok(Outcome::Vanished),
+                RespValue::Error(message) => {
+                    debug!("DUMP failed ({message}); re-creating keys instead");
+                    self.recreate = true;
+                }
+                other => return Err(unexpected_reply("DUMP", key, &other)),
+            }
+        }
+
+        let Some(value) = read_value(&mut self.source, key).await? else {
+            return Ok(Outcome::Vanished);
+        };
+        let mut commands = value.write_commands(key)?;
+        if ttl > 0 {
+            commands.push(vec![
+                b"PEXPIRE".to_vec(),
+                key.to_vec(),
+                ttl.to_string().into_bytes(),
+            ]);
+        }
+        for command in commands {
+            if let RespValue::Error(message) = self.destination.command(&command).await? {
+                return Err(CliError::new(format!(
+                    "Could not write {}: {message}",
+                    String::from_utf8_lossy(key)
+                )));
+            }
+        }
+        Ok(Outcome::Recreated)
+    }
+
+    /// Describes each key whose value in the destination doesn't match the source's.
+    async fn verify_keys(&mut self, keys: Vec<Vec<u8>>) -> Result<Vec<String>, CliError> {
+        let mut mismatches = Vec::new();
+        for key in keys {
+            let source = read_value(&mut self.source, &key).await?;
+            let destination = read_value(&mut self.destination, &key).await?;
+            let mismatch = match (source, destination) {
+                (None, _) => None,
+                (Some(_), None) => Some("missing from the destination"),
+                (Some(source), Some(destination)) if source != destination => {
+                    Some("different in the destination")
+                }
+                _ => None,
+            };
+            if let Some(mismatch) = mismatch {
+                mismatches.push(format!("{}: {mismatch}", String::from_utf8_lossy(&key)));
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+async fn scan(
+    connection: &mut AnyRespConnection,
+    cursor: &str,
+) -> Result<(String, Vec<Vec<u8>>), CliError> {
+    let reply = connection
+        .command(&["SCAN", cursor, "COUNT", &SCAN_COUNT.to_string()])
+        .await?;
+    match reply {
+        RespValue::Array(mut parts) if parts.len() == 2 => {
+            let keys = bulk_items(parts.remove(1), "SCAN", cursor.as_bytes())?;
+            match parts.remove(0) {
+                RespValue::Bulk(next) => Ok((String::from_utf8_lossy(&next).to_string(), keys)),
+                other => Err(unexpected_reply("SCAN", cursor.as_bytes(), &other)),
+            }
+        }
+        other => Err(unexpected_reply("SCAN", cursor.as_bytes(), &other)),
+    }
+}
+
+/// The nodes that hold the source's keys: the source itself, or each primary if it's a Redis
+/// Cluster, since SCAN only walks the keys of the node it's sent to.
+async fn source_nodes(source: &RespEndpoint) -> Result<Vec<RespEndpoint>, CliError> {
+    let mut connection = source.connect().await?;
+    // Servers without cluster support, or that turn INFO down, aren't clusters.
+    let cluster_enabled = match connection.command(&["INFO", "cluster"]).await? {
+        RespValue::Bulk(info) => String::from_utf8_lossy(&info)
+            .lines()
+            .any(|line| line.trim() == "cluster_enabled:1"),
+        _ => false,
+    };
+    if !cluster_enabled {
+        return Ok(vec![source.clone()]);
+    }
+    let nodes = match connection.command(&["CLUSTER", "NODES"]).await? {
+        RespValue::Bulk(nodes) => String::from_utf8_lossy(&nodes).to_string(),
+        other => {
+            return Err(CliError::new(format!(
+                "Unexpected reply to CLUSTER NODES: {}",
+                other.display()
+            )))
+        }
+    };
+    let primaries = cluster_primaries(&nodes, &source.host);
+    if primaries.is_empty() {
+        return Err(CliError::new(format!(
+            "{source} is a Redis Cluster, but CLUSTER NODES lists no primaries serving slots"
+        )));
+    }
+    Ok(primaries
+        .into_iter()
+        .map(|(host, port)| RespEndpoint {
+            host,
+            port,
+            ..source.clone()
+        })
+        .collect())
+}
+
+/// The `(host, port)` of each healthy primary that serves slots, from a CLUSTER NODES reply,
+/// sorted so every run scans them in the same order. A node's announced hostname is used when
+/// it has one, so TLS certificates match; a node that doesn't know its own address is
+/// `default_host`.
+fn cluster_primaries(nodes: &str, default_host: &str) -> Vec<(String, u16)> {
+    let mut primaries: Vec<(String, u16)> = nodes
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let flags: Vec<&str> = fields.get(2)?.split(',').collect();
+            let serves_slots = fields.len() > 8;
+            let healthy = !flags
+                .iter()
+                .any(|flag| matches!(*flag, "fail" | "noaddr" | "handshake"));
+            if !flags.contains(&"master") || !healthy || !serves_slots {
+                return None;
+            }
+            let (address, bus) = fields[1].split_once('@')?;
+            let (ip, port) = address.rsplit_once(':')?;
+            let hostname = bus.split_once(',').map(|(_, hostname)| hostname);
+            let host = [hostname.unwrap_or_default(), ip]
+                .into_iter()
+                .find(|host| !host.is_empty())
+                .unwrap_or(default_host);
+            Some((host.to_string(), port.parse().ok()?))
+        })
+        .collect();
+    primaries.sort();
+    primaries
+}
+
+fn node_address(node: &RespEndpoint) -> String {
+    format!("{}:{}", node.host, node.port)
+}
+
+/// Points the scanner, and each worker's source connection, at `node`.
+async fn connect_to_node(
+    node: &RespEndpoint,
+    scanner: &mut AnyRespConnection,
+    workers: &mut [Worker],
+) -> Result<(), CliError> {
+    *scanner = node.connect().await?;
+    for worker in workers {
+        worker.source = node.connect().await?;
+    }
+    Ok(())
+}
+
+/// Deals keys out to `workers` buckets in turn.
+fn deal(keys: Vec<Vec<u8>>, workers: usize) -> Vec<Vec<Vec<u8>>> {
+    let mut buckets = vec![Vec::new(); workers];
+    for (i, key) in keys.into_iter().enumerate() {
+        buckets[i % workers].push(key);
+    }
+    buckets
+}
+
+fn load_checkpoint(
+    path: &str,
+    restart: bool,
+    source: &RespEndpoint,
+    destination: &RespEndpoint,
+) -> Result<Checkpoint, CliError> {
+    let fresh = Checkpoint {
+        source: source.to_string(),
+        destination: destination.to_string(),
+        node: None,
+        cursor: "0".to_string(),
+        copied: 0,
+    };
+    if restart {
+        return Ok(fresh);
+    }
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(fresh),
+        Err(e) => {
+            return Err(CliError::new(format!(
+                "Could not read checkpoint {path}: {e}"
+            )))
+        }
+    };
+    let checkpoint: Checkpoint = serde_json::from_str(&contents)
+        .map_err(|e| CliError::new(format!("Invalid checkpoint {path}: {e}")))?;
+    if checkpoint.source != fresh.source || checkpoint.destination != fresh.destination {
+        return Err(CliError::new(format!(
+            "{path} is a checkpoint for copying {} to {}. Pass --restart to start over, \
+             or --checkpoint to use another file",
+            checkpoint.source, checkpoint.destination
+        )));
+    }
+    Ok(checkpoint)
+}
+
+fn save_checkpoint(path: &str, checkpoint: &Checkpoint) -> Result<(), CliError> {
+    // Written aside and renamed, so an interruption never leaves half a checkpoint.
+    let partial = format!("{path}.partial");
+    std::fs::write(
+        &partial,
+        serde_json::to_string(checkpoint).expect("checkpoints always serialize"),
+    )
+    .and_then(|_| std::fs::rename(&partial, path))
+    .map_err(|e| CliError::new(format!("Could not write checkpoint {path}: {e}")))
+}
+
+/// Copies every key in `source` to `destination`, with its TTL, then checks that the
+/// destination has the same values.
+pub async fn migrate_database(
+    source: RespEndpoint,
+    destination: RespEndpoint,
+    options: MigrateOptions,
+) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "Migrate",
+        &[
+            ("source", source.to_string()),
+            ("destination", destination.to_string()),
+            ("checkpoint", options.checkpoint_path.clone()),
+        ],
+    ) {
+        return Ok(());
+    }
+    let mut checkpoint = load_checkpoint(
+        &options.checkpoint_path,
+        options.restart,
+        &source,
+        &destination,
+    )?;
+    if checkpoint.copied > 0 {
+        console_data!(
+            "Resuming from {}: {} key(s) already copied",
+            options.checkpoint_path,
+            checkpoint.copied
+        );
+    }
+
+    let nodes = source_nodes(&source).await?;
+    let first_node = match &checkpoint.node {
+        None => 0,
+        Some(node) => nodes
+            .iter()
+            .position(|candidate| node_address(candidate) == *node)
+            .ok_or_else(|| {
+                CliError::new(format!(
+                    "{} stopped while copying from {node}, which is no longer one of the \
+                     source's primaries. Pass --restart to start over",
+                    options.checkpoint_path
+                ))
+            })?,
+    };
+    if nodes.len() > 1 {
+        console_data!(
+            "{source} is a Redis Cluster; copying from each of its {} primaries",
+            nodes.len()
+        );
+    }
+
+    let mut scanner = nodes[first_node].connect().await?;
+    let mut workers = Vec::with_capacity(options.concurrency);
+    for _ in 0..options.concurrency.max(1) {
+        workers.push(Worker {
+            source: nodes[first_node].connect().await?,
+            destination: destination.connect().await?,
+            recreate: options.recreate,
+        });
+    }
+
+    let spinner =
+        ProgressBar::new_spinner().with_message(format!("Copying {source} to {destination}"));
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    let already_copied = checkpoint.copied;
+    let mut counts = CopyCounts::default();
+    let copied: Result<(), CliError> = async {
+        for (index, node) in nodes.iter().enumerate().skip(first_node) {
+            if index > first_node {
+                connect_to_node(node, &mut scanner, &mut workers).await?;
+            }
+            checkpoint.node = Some(node_address(node));
+            loop {
+                let (next, keys) = scan(&mut scanner, &checkpoint.cursor).await?;
+                let batches = deal(keys, workers.len());
+                let results = try_join_all(
+                    workers
+                        .iter_mut()
+                        .zip(batches)
+                        .map(|(worker, keys)| worker.copy_keys(keys)),
+                )
+                .await?;
+                results.into_iter().for_each(|result| counts.add(result));
+                checkpoint.cursor = next;
+                checkpoint.copied = already_copied + counts.copied();
+                let node_done = checkpoint.cursor == "0";
+                if node_done {
+                    // A resumed run starts on the next node, rather than rescanning this one.
+                    if let Some(next_node) = nodes.get(index + 1) {
+                        checkpoint.node = Some(node_address(next_node));
+                    }
+                }
+                save_checkpoint(&options.checkpoint_path, &checkpoint)?;
+                spinner.set_message(format!("Copied {} key(s)", checkpoint.copied));
+                if node_done {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+    .await;
+    spinner.finish_and_clear();
+    copied?;
+    std::fs::remove_file(&options.checkpoint_path).ok();
+
+    let mut summary = format!(
+        "Copied {} key(s) from {source} to {destination}",
+        checkpoint.copied
+    );
+    if counts.recreated > 0 {
+        summary.push_str(&format!(
+            ", re-creating {} from their values",
+            counts.recreated
+        ));
+    }
+    if counts.vanished > 0 {
+        summary.push_str(&format!(
+            "; {} expired or were deleted before they could be copied",
+            counts.vanished
+        ));
+    }
+    console_data!("{summary}");
+
+    if options.verify {
+        verify(&nodes, &mut scanner, &mut workers).await?;
+    }
+    Ok(())
+}
+
+async fn verify(
+    nodes: &[RespEndpoint],
+    scanner: &mut AnyRespConnection,
+    workers: &mut [Worker],
+) -> Result<(), CliError> {
+    let spinner = ProgressBar::new_spinner().with_message("Verifying");
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    let mut checked = 0;
+    let mut mismatches = Vec::new();
+    let verified: Result<(), CliError> = async {
+        for node in nodes {
+            // The copy left a single-node source connected; a cluster's nodes are revisited.
+            if nodes.len() > 1 {
+                connect_to_node(node, scanner, workers).await?;
+            }
+            let mut cursor = "0".to_string();
+            loop {
+                let (next, keys) = scan(scanner, &cursor).await?;
+                checked += keys.len();
+                let batches = deal(keys, workers.len());
+                let results = try_join_all(
+                    workers
+                        .iter_mut()
+                        .zip(batches)
+                        .map(|(worker, keys)| worker.verify_keys(keys)),
+                )
+                .await?;
+                mismatches.extend(results.into_iter().flatten());
+                spinner.set_message(format!("Verified {checked} key(s)"));
+                cursor = next;
+                if cursor == "0" {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+    .await;
+    spinner.finish_and_clear();
+    verified?;
+
+    if mismatches.is_empty() {
+        console_data!("Verified {checked} key(s)");
+        return Ok(());
+    }
+    // Workers finish in any order; sorted, the report reads the same from run to run.
+    mismatches.sort();
+    let mut message = format!(
+        "{} of {checked} key(s) don't match the source:",
+        mismatches.len()
+    );
+    for mismatch in mismatches.iter().take(MAX_REPORTED_MISMATCHES) {
+        message.push_str(&format!("\n  {mismatch}"));
+    }
+    if mismatches.len() > MAX_REPORTED_MISMATCHES {
+        message.push_str(&format!(
+            "\n  ...and {} more",
+            mismatches.len() - MAX_REPORTED_MISMATCHES
+        ));
+    }
+    Err(CliError::new(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::database::resp::tests::answer;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum FakeValue {
+        String(String),
+        Hash(BTreeMap<String, String>),
+        List(Vec<String>),
+    }
+
+    /// Keys, with their values and PTTLs, in SCAN order.
+    type FakeData = Arc<Mutex<BTreeMap<String, (FakeValue, i64)>>>;
+
+    /// A CLUSTER NODES reply, once the cluster's nodes are listening; `None` for a server
+    /// that isn't in a cluster.
+    type FakeClusterNodes = Arc<Mutex<Option<String>>>;
+
+    /// Just enough of a RESP server to migrate strings, hashes and lists from or to. Its DUMP
+    /// payloads are JSON, and with `restore: false` it turns RESTORE down.
+    async fn serve_fake_valkey(data: FakeData, restore: bool) -> RespEndpoint {
+        serve_fake_valkey_node(data, restore, Arc::default()).await
+    }
+
+    /// Like `serve_fake_valkey`, but a node in the cluster that `cluster` describes.
+    async fn serve_fake_valkey_node(
+        data: FakeData,
+        restore: bool,
+        cluster: FakeClusterNodes,
+    ) -> RespEndpoint {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("should bind a local port");
+        let port = listener.local_addr().expect("should have address").port();
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.expect("should accept");
+                let data = data.clone();
+                let cluster = cluster.clone();
+                tokio::spawn(async move {
+                    answer(socket, |args| {
+                        fake_command(
+                            &mut data.lock().expect("not poisoned"),
+                            restore,
+                            cluster.lock().expect("not poisoned").as_deref(),
+                            args,
+                        )
+                    })
+                    .await
+                });
+            }
+        });
+        RespEndpoint {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: false,
+            user: None,
+            password: None,
+            database: None,
+            ca_bundle_paths: vec![],
+        }
+    }
+
+    fn fake_command(
+        data: &mut BTreeMap<String, (FakeValue, i64)>,
+        restore: bool,
+        cluster: Option<&str>,
+        args: &[String],
+    ) -> RespValue {
+        let ok = RespValue::Simple("OK".to_string());
+        let bulk = |text: &str| RespValue::Bulk(text.as_bytes().to_vec());
+        let bulks = |items: Vec<String>| RespValue::Array(items.iter().map(|i| bulk(i)).collect());
+        let key = args.get(1).cloned().unwrap_or_default();
+        match args[0].as_str() {
+            "INFO" => bulk(&format!(
+                "# Cluster\r\ncluster_enabled:{}\r\n",
+                u8::from(cluster.is_some())
+            )),
+            "CLUSTER" => match cluster {
+                Some(nodes) => bulk(nodes),
+                None => RespValue::Error("ERR This instance has cluster support disabled".into()),
+            },
+            "SCAN" => {
+                let start: usize = args[1].parse().expect("numeric cursor");
+                let count: usize = args[3].parse().expect("numeric count");
+                let keys: Vec<String> = data.keys().skip(start).take(count).cloned().collect();
+                let next = if start + count >= data.len() {
+                    0
+                } else {
+                    start + count
+                };
+                RespValue::Array(vec![bulk(&next.to_string()), bulks(keys)])
+            }
+            "PTTL" => RespValue::Integer(data.get(&key).map_or(-2, |(_, ttl)| *ttl)),
+            "TYPE" => RespValue::Simple(
+                match data.get(&key) {
+                    None => "none",
+                    Some((FakeValue::String(_), _)) => "string",
+                    Some((FakeValue::Hash(_), _)) => "hash",
+                    Some((FakeValue::List(_), _)) => "list",
+                }
+                .to_string(),
+            ),
+            "DUMP" => data.get(&key).map_or(RespValue::Null, |(value, _)| {
+                bulk(&serde_json::to_string(value).expect("serializable"))
+            }),
+            "RESTORE" if restore => {
+                let ttl: i64 = args[2].parse().expect("numeric ttl");
+                let value = serde_json::from_str(&args[3]).expect("a fake DUMP payload");
+                data.insert(key, (value, if ttl == 0 { -1 } else { ttl }));
+                ok
+            }
+            "GET" => match data.get(&key) {
+                Some((FakeValue::String(value), _)) => bulk(value),
+                _ => RespValue::Null,
+            },
+            "SET" => {
+                data.insert(key, (FakeValue::String(args[2].clone()), -1));
+                ok
+            }
+            "HGETALL" => match data.get(&key) {
+                Some((FakeValue::Hash(hash), _)) => bulks(
+                    hash.iter()
+                        .flat_map(|(field, value)| [field.clone(), value.clone()])
+                        .collect(),
+                ),
+                _ => RespValue::Array(vec![]),
+            },
+            "HSET" => {
+                let entry = data
+                    .entry(key)
+                    .or_insert((FakeValue::Hash(BTreeMap::new()), -1));
+                if let FakeValue::Hash(hash) = &mut entry.0 {
+                    for pair in args[2..].chunks(2) {
+                        hash.insert(pair[0].clone(), pair[1].clone());
+                    }
+                }
+                RespValue::Integer(1)
+            }
+            "LRANGE" => match data.get(&key) {
+                Some((FakeValue::List(items), _)) => bulks(items.clone()),
+                _ => RespValue::Array(vec![]),
+            },
+            "RPUSH" => {
+                let entry = data.entry(key).or_insert((FakeValue::List(vec![]), -1));
+                if let FakeValue::List(items) = &mut entry.0 {
+                    items.extend(args[2..].iter().cloned());
+                }
+                RespValue::Integer(1)
+            }
+            "DEL" => RespValue::Integer(data.remove(&key).is_some() as i64),
+            "PEXPIRE" => match data.get_mut(&key) {
+                Some(entry) => {
+                    entry.1 = args[2].parse().expect("numeric ttl");
+                    RespValue::Integer(1)
+                }
+                None => RespValue::Integer(0),
+            },
+            command => RespValue::Error(format!("ERR unknown command '{command}'")),
+        }
+    }
+
+    fn sample_data() -> BTreeMap<String, (FakeValue, i64)> {
+        BTreeMap::from([
+            (
+                "greeting".to_string(),
+                (FakeValue::String("hello".to_string()), 5000),
+            ),
+            (
+                "user:1".to_string(),
+                (
+                    FakeValue::Hash(BTreeMap::from([
+                        ("name".to_string(), "Ada".to_string()),
+                        ("plan".to_string(), "pro".to_string()),
+                    ])),
+                    -1,
+                ),
+            ),
+            (
+                "queue".to_string(),
+                (
+                    FakeValue::List(vec!["a".to_string(), "b".to_string(), "a".to_string()]),
+                    -1,
+                ),
+            ),
+        ])
+    }
+
+    fn options(dir: &tempdir::TempDir) -> MigrateOptions {
+        MigrateOptions {
+            concurrency: 2,
+            checkpoint_path: dir
+                .path()
+                .join("migrate.checkpoint")
+                .to_string_lossy()
+                .to_string(),
+            restart: false,
+            recreate: false,
+            verify: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_are_restored_with_their_ttls() {
+        let source_data: FakeData = Arc::new(Mutex::new(sample_data()));
+        let destination_data: FakeData = Arc::default();
+        let source = serve_fake_valkey(source_data.clone(), true).await;
+        let destination = serve_fake_valkey(destination_data.clone(), true).await;
+        let dir = tempdir::TempDir::new("momento-cli-migrate").expect("temp dir");
+        let options = options(&dir);
+        let checkpoint_path = options.checkpoint_path.clone();
+
+        migrate_database(source, destination, options)
+            .await
+            .expect("migration should succeed");
+
+        assert_eq!(*destination_data.lock().unwrap(), sample_data());
+        assert!(
+            !std::path::Path::new(&checkpoint_path).exists(),
+            "a finished migration removes its checkpoint"
+        );
+    }
+
+    #[tokio::test]
+    async fn keys_are_recreated_when_the_destination_cannot_restore() {
+        let source_data: FakeData = Arc::new(Mutex::new(sample_data()));
+        let destination_data: FakeData = Arc::default();
+        let source = serve_fake_valkey(source_data.clone(), true).await;
+        let destination = serve_fake_valkey(destination_data.clone(), false).await;
+        let dir = tempdir::TempDir::new("momento-cli-migrate").expect("temp dir");
+
+        migrate_database(source, destination, options(&dir))
+            .await
+            .expect("migration should succeed");
+
+        assert_eq!(*destination_data.lock().unwrap(), sample_data());
+    }
+
+    #[tokio::test]
+    async fn migrations_resume_from_their_checkpoint_and_verification_catches_gaps() {
+        let source_data: FakeData = Arc::new(Mutex::new(
+            (0..5)
+                .map(|i| (format!("k{i}"), (FakeValue::String(i.to_string()), -1)))
+                .collect(),
+        ));
+        let destination_data: FakeData = Arc::default();
+        let source = serve_fake_valkey(source_data.clone(), true).await;
+        let destination = serve_fake_valkey(destination_data.clone(), true).await;
+        let dir = tempdir::TempDir::new("momento-cli-migrate").expect("temp dir");
+        let options = options(&dir);
+        // As if an earlier run copied k0..k2 to a destination that's since lost them.
+        save_checkpoint(
+            &options.checkpoint_path,
+            &Checkpoint {
+                source: source.to_string(),
+                destination: destination.to_string(),
+                node: None,
+                cursor: "3".to_string(),
+                copied: 3,
+            },
+        )
+        .expect("should write checkpoint");
+
+        let err = migrate_database(source, destination, options)
+            .await
+            .expect_err("verification should fail");
+
+        assert_eq!(
+            destination_data.lock().unwrap().keys().collect::<Vec<_>>(),
+            vec!["k3", "k4"]
+        );
+        assert_eq!(
+            err.msg,
+            "3 of 5 key(s) don't match the source:\n  \
+             k0: missing from the destination\n  \
+             k1: missing from the destination\n  \
+             k2: missing from the destination"
+        );
+    }
+
+    #[tokio::test]
+    async fn every_primary_of_a_cluster_is_copied_and_verified() {
+        let key = |name: &str| (name.to_string(), (FakeValue::String(name.to_string()), -1));
+        let shards: Vec<FakeData> = [["a1", "a2"], ["b1", "b2"]]
+            .iter()
+            .map(|keys| Arc::new(Mutex::new(keys.iter().map(|name| key(name)).collect())))
+            .collect();
+        let cluster: FakeClusterNodes = Arc::default();
+        let mut primaries = Vec::new();
+        for shard in &shards {
+            primaries.push(serve_fake_valkey_node(shard.clone(), true, cluster.clone()).await);
+        }
+        let [first, second] = [&primaries[0], &primaries[1]].map(node_address);
+        *cluster.lock().unwrap() = Some(format!(
+            "1 {first}@16379 myself,master - 0 0 1 connected 0-8191\n\
+             2 {second}@16379 master - 0 0 2 connected 8192-16383\n\
+             3 127.0.0.1:1@16379 slave 1 0 0 3 connected\n"
+        ));
+        let destination_data: FakeData = Arc::default();
+        let destination = serve_fake_valkey(destination_data.clone(), true).await;
+        let dir = tempdir::TempDir::new("momento-cli-migrate").expect("temp dir");
+
+        migrate_database(primaries[0].clone(), destination, options(&dir))
+            .await
+            .expect("migration should succeed");
+
+        assert_eq!(
+            destination_data.lock().unwrap().keys().collect::<Vec<_>>(),
+            vec!["a1", "a2", "b1", "b2"]
+        );
+
+        // A key the copy missed on the second shard fails verification.
+        destination_data.lock().unwrap().remove("b2");
+        let destination = serve_fake_valkey(destination_data.clone(), true).await;
+        shards[1]
+            .lock()
+            .unwrap()
+            .insert("b3".to_string(), key("b3").1);
+        let mut workers = vec![Worker {
+            source: primaries[0].connect().await.expect("connects"),
+            destination: destination.connect().await.expect("connects"),
+            recreate: false,
+        }];
+        let mut scanner = primaries[0].connect().await.expect("connects");
+        let err = verify(&primaries, &mut scanner, &mut workers)
+            .await
+            .expect_err("verification should fail");
+        assert_eq!(
+            err.msg,
+            "2 of 5 key(s) don't match the source:\n  \
+             b2: missing from the destination\n  \
+             b3: missing from the destination"
+        );
+    }
+
+    #[test]
+    fn cluster_primaries_are_healthy_masters_with_slots() {
+        let nodes = "\
+            07c3 10.0.0.1:6379@16379 myself,master - 0 0 1 connected 0-5460\n\
+            67ed 10.0.0.2:6380@16380,shard-2.example.com master - 0 0 2 connected 5461-10922\n\
+            292f :6381@16381 master - 0 0 3 connected 10923-16383\n\
+            6ec2 10.0.0.4:6379@16379 slave 07c3 0 0 1 connected\n\
+            824f 10.0.0.5:6379@16379 master,fail - 0 0 4 disconnected 1-2\n\
+            9a1b 10.0.0.6:6379@16379 master - 0 0 5 connected\n";
+        assert_eq!(
+            cluster_primaries(nodes, "config.example.com"),
+            vec![
+                ("10.0.0.1".to_string(), 6379),
+                ("config.example.com".to_string(), 6381),
+                ("shard-2.example.com".to_string(), 6380),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn checkpoints_only_resume_the_same_migration() {
+        let dir = tempdir::TempDir::new("momento-cli-migrate").expect("temp dir");
+        let path = dir.path().join("checkpoint").to_string_lossy().to_string();
+        let endpoint = |host: &str| {
+            RespEndpoint::from_url(&format!("redis://{host}"), vec![]).expect("valid URL")
+        };
+        save_checkpoint(
+            &path,
+            &Checkpoint {
+                source: endpoint("old").to_string(),
+                destination: endpoint("db").to_string(),
+                node: None,
+                cursor: "42".to_string(),
+                copied: 100,
+            },
+        )
+        .expect("should write checkpoint");
+
+        let resumed =
+            load_checkpoint(&path, false, &endpoint("old"), &endpoint("db")).expect("resumes");
+        assert_eq!(resumed.cursor, "42");
+        assert!(
+            load_checkpoint(&path, false, &endpoint("other"), &endpoint("db"))
+                .unwrap_err()
+                .msg
+                .contains("Pass --restart to start over")
+        );
+        assert_eq!(
+            load_checkpoint(&path, true, &endpoint("other"), &endpoint("db"))
+                .expect("restarts")
+                .cursor,
+            "0"
+        );
+    }
+
+    #[test]
+    fn values_are_rewritten_in_bounded_commands() {
+        let items: Vec<Vec<u8>> = (0..1001).map(|i| i.to_string().into_bytes()).collect();
+        let commands = KeyValue::List(items)
+            .write_commands(b"big")
+            .expect("lists can be re-created");
+        let sizes: Vec<(String, usize)> = commands
+            .iter()
+            .map(|command| {
+                (
+                    String::from_utf8_lossy(&command[0]).to_string(),
+                    command.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                ("DEL".to_string(), 2),
+                ("RPUSH".to_string(), 502),
+                ("RPUSH".to_string(), 502),
+                ("RPUSH".to_string(), 3),
+            ]
+        );
+
+        let zadd = KeyValue::SortedSet(vec![b"m".to_vec(), b"1.5".to_vec()])
+            .write_commands(b"scores")
+            .expect("sorted sets can be re-created");
+        assert_eq!(
+            zadd[1],
+            vec![
+                b"ZADD".to_vec(),
+                b"scores".to_vec(),
+                b"1.5".to_vec(),
+                b"m".to_vec()
+            ]
+        );
+        assert!(KeyValue::Other("stream".to_string())
+            .write_commands(b"events")
+            .is_err());
+    }
+}
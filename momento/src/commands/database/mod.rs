@@ -1,2 +1,4 @@
 pub mod database_cli;
+mod migrate;
+mod resp;
 mod utils;
@@ -0,0 +1,258 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::{Duration, SystemTime},
+};
+
+use momento::{functions::WasmSource, FunctionClient};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+};
+
+use crate::{
+    commands::functions::{function_cli::put_function, utils::validate_wasm_component},
+    error::CliError,
+    utils::console::console_info,
+};
+
+const WASM_TARGET: &str = "wasm32-wasip2";
+
+/// How often `--watch` looks for changed sources.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct DeployOptions {
+    pub cache_name: String,
+    pub name: Option<String>,
+    pub manifest_path: String,
+    pub description: Option<String>,
+    pub environment_variables: Vec<(String, String)>,
+    pub watch: bool,
+}
+
+/// A `.wasm` file that cargo built from a `cdylib` target.
+#[derive(Debug, PartialEq)]
+struct WasmArtifact {
+    target_name: String,
+    path: PathBuf,
+}
+
+pub async fn deploy(client: FunctionClient, options: DeployOptions) -> Result<(), CliError> {
+    if !options.watch {
+        return deploy_once(&client, &options).await;
+    }
+
+    let crate_dir = crate_dir(&options.manifest_path);
+    let mut last_change = latest_modification(&crate_dir)?;
+    if let Err(e) = deploy_once(&client, &options).await {
+        console_info!("Deploy failed: {}", e.msg);
+    }
+    loop {
+        console_info!("Watching {} for changes...", crate_dir.display());
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+            let modified = latest_modification(&crate_dir)?;
+            if modified > last_change {
+                last_change = modified;
+                break;
+            }
+        }
+        console_info!("Sources changed, redeploying");
+        if let Err(e) = deploy_once(&client, &options).await {
+            console_info!("Deploy failed: {}", e.msg);
+        }
+    }
+}
+
+async fn deploy_once(client: &FunctionClient, options: &DeployOptions) -> Result<(), CliError> {
+    let artifact = build(&options.manifest_path).await?;
+    let binary = fs::read(&artifact.path).map_err(|e| {
+        CliError::new(format!(
+            "Could not read the build output {}: {e}",
+            artifact.path.display()
+        ))
+    })?;
+    validate_wasm_component(&binary)
+        .map_err(|e| CliError::new(format!("{}: {}", artifact.path.display(), e.msg)))?;
+
+    put_function(
+        client,
+        options.cache_name.clone(),
+        options.name.clone().unwrap_or(artifact.target_name),
+        WasmSource::Inline(binary),
+        options.description.clone(),
+        options.environment_variables.clone(),
+        None,
+    )
+    .await
+}
+
+/// Runs `cargo build` for the function's target, leaving cargo's diagnostics on stderr,
+/// and finds the component it produced.
+async fn build(manifest_path: &str) -> Result<WasmArtifact, CliError> {
+    console_info!("Building {manifest_path} for {WASM_TARGET}...");
+    let mut child = Command::new("cargo")
+        .args([
+            "build",
+            "--release",
+            "--target",
+            WASM_TARGET,
+            "--message-format=json-render-diagnostics",
+            "--manifest-path",
+            manifest_path,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| CliError::new(format!("Could not run cargo: {e}")))?;
+
+    let mut artifacts = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| CliError::new(format!("Could not read cargo's output: {e}")))?
+        {
+            artifacts.extend(parse_artifact(&line));
+        }
+    }
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| CliError::new(format!("Could not run cargo: {e}")))?;
+    if !status.success() {
+        return Err(CliError::new(format!(
+            "cargo build failed ({status}); see its output above"
+        )));
+    }
+    choose_artifact(artifacts)
+}
+
+/// Reads a `compiler-artifact` message from cargo's JSON output, if it's for a `.wasm` cdylib.
+fn parse_artifact(line: &str) -> Option<WasmArtifact> {
+    let message: Value = serde_json::from_str(line).ok()?;
+    if message["reason"] != "compiler-artifact" {
+        return None;
+    }
+    let target = &message["target"];
+    let is_cdylib = target["kind"]
+        .as_array()?
+        .iter()
+        .any(|kind| kind == "cdylib");
+    if !is_cdylib {
+        return None;
+    }
+    let path = message["filenames"]
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .find(|filename| filename.ends_with(".wasm"))?;
+    Some(WasmArtifact {
+        target_name: target["name"].as_str()?.to_string(),
+        path: PathBuf::from(path),
+    })
+}
+
+fn choose_artifact(mut artifacts: Vec<WasmArtifact>) -> Result<WasmArtifact, CliError> {
+    match artifacts.len() {
+        0 => Err(CliError::new(
+            "cargo built no .wasm files. Add `crate-type = [\"cdylib\"]` to the [lib] section of your Cargo.toml",
+        )),
+        1 => Ok(artifacts.remove(0)),
+        _ => Err(CliError::new(format!(
+            "cargo built more than one .wasm file ({}). Point --manifest-path at a single function's Cargo.toml",
+            artifacts
+                .iter()
+                .map(|artifact| artifact.target_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+fn crate_dir(manifest_path: &str) -> PathBuf {
+    match Path::new(manifest_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// The newest modification time of any file under `dir`, skipping build output and hidden
+/// directories so a build doesn't trigger another.
+fn latest_modification(dir: &Path) -> Result<SystemTime, CliError> {
+    let mut latest = SystemTime::UNIX_EPOCH;
+    let entries = fs::read_dir(dir)
+        .map_err(|e| CliError::new(format!("Could not watch {}: {e}", dir.display())))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified = if metadata.is_dir() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name == "target" || name.starts_with('.') {
+                continue;
+            }
+            latest_modification(&path)?
+        } else {
+            metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)
+        };
+        latest = latest.max(modified);
+    }
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_cdylib_wasm_artifacts_are_parsed() {
+        let cdylib = r#"{"reason":"compiler-artifact","target":{"kind":["cdylib"],"name":"my_function"},"filenames":["/work/target/wasm32-wasip2/release/my_function.wasm"]}"#;
+        assert_eq!(
+            parse_artifact(cdylib),
+            Some(WasmArtifact {
+                target_name: "my_function".to_string(),
+                path: PathBuf::from("/work/target/wasm32-wasip2/release/my_function.wasm"),
+            })
+        );
+
+        let dependency = r#"{"reason":"compiler-artifact","target":{"kind":["lib"],"name":"serde"},"filenames":["/work/target/wasm32-wasip2/release/deps/libserde.rlib"]}"#;
+        assert_eq!(parse_artifact(dependency), None);
+        assert_eq!(
+            parse_artifact(r#"{"reason":"build-finished","success":true}"#),
+            None
+        );
+        assert_eq!(parse_artifact("not json"), None);
+    }
+
+    #[test]
+    fn exactly_one_artifact_is_deployable() {
+        let artifact = |name: &str| WasmArtifact {
+            target_name: name.to_string(),
+            path: PathBuf::from(format!("{name}.wasm")),
+        };
+        assert!(choose_artifact(vec![]).unwrap_err().msg.contains("cdylib"));
+        assert_eq!(
+            choose_artifact(vec![artifact("a")]).expect("one artifact"),
+            artifact("a")
+        );
+        assert!(choose_artifact(vec![artifact("a"), artifact("b")])
+            .unwrap_err()
+            .msg
+            .contains("(a, b)"));
+    }
+
+    #[test]
+    fn crate_dir_is_the_manifest_directory() {
+        assert_eq!(crate_dir("Cargo.toml"), PathBuf::from("."));
+        assert_eq!(
+            crate_dir("functions/echo/Cargo.toml"),
+            PathBuf::from("functions/echo")
+        );
+    }
+}
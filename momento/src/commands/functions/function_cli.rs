@@ -1,27 +1,96 @@
 use momento::{
+    cache::GetResponse,
     functions::{
         CurrentFunctionVersion, FunctionMetricsConfigChange, ListFunctionVersionsRequest,
         ListFunctionsRequest, ListWasmsRequest, PutFunctionConfigRequest, PutFunctionRequest,
         PutWasmRequest, WasmSource,
     },
-    FunctionClient,
+    CredentialProvider, FunctionClient,
 };
 
 use crate::{
+    commands::functions::registry::FunctionRegistry,
     commands::functions::utils::{
-        build_invocation_headers, build_invocation_path, call_function_api, format_metrics_config,
-        read_wasm_file, InvocationOptions,
+        build_invocation_headers, build_invocation_path, call_function_api, diff_versions,
+        display_env_value, environment_snapshot, find_version, format_current_function_version,
+        format_environment, format_metrics_config, format_metrics_config_change,
+        format_response_head, format_wasm_source, invocation_exit_code, merge_environment,
+        parse_cache_reference, parse_env_file, plan_wasm_prune, previous_version,
+        read_invocation_body, read_wasm_file, wasm_references, FunctionVersions, InvocationOptions,
+        UploadedWasm, VersionSnapshot,
     },
     error::CliError,
-    utils::console::console_data,
+    utils::{
+        client::{get_cache_client, interact_with_momento},
+        console::{console_data, console_info},
+        dry_run::skip_sdk_request,
+        trace::finish_trace,
+    },
 };
 
 use http::Method;
 use log::info;
+use std::fs;
+use std::io::{self, Write};
+use std::process::exit;
 use std::str::FromStr; // to use http::Method::from_str
 
+/// Gathers a function's environment from `--env-file`, then `--env-from-cache`, then `-E`,
+/// with later sources overriding earlier ones.
+pub async fn collect_environment(
+    credential_provider: CredentialProvider,
+    cache_name: &str,
+    env_file: Option<String>,
+    env_from_cache: Vec<String>,
+    environment_variables: Vec<(String, String)>,
+) -> Result<Vec<(String, String)>, CliError> {
+    let from_file = match env_file {
+        Some(path) => {
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| CliError::new(format!("Could not read --env-file {path}: {e}")))?;
+            parse_env_file(&contents, |name| std::env::var(name).ok())
+                .map_err(|e| CliError::new(format!("{path} {}", e.msg)))?
+        }
+        None => vec![],
+    };
+
+    let mut from_cache = Vec::new();
+    if !env_from_cache.is_empty() {
+        let client = get_cache_client(credential_provider).await?;
+        for reference in env_from_cache {
+            let (name, key) = parse_cache_reference(&reference);
+            if skip_sdk_request(
+                "Get",
+                &[("cache_name", cache_name.to_string()), ("key", key.clone())],
+            ) {
+                from_cache.push((name, format!("<value of cache key {key}>")));
+                continue;
+            }
+            let value = match interact_with_momento("getting...", client.get(cache_name, key.as_str()))
+                .await?
+            {
+                GetResponse::Hit { value } => String::try_from(value).map_err(|_| {
+                    CliError::new(format!("Cache key {key} doesn't hold UTF-8 text"))
+                })?,
+                GetResponse::Miss => {
+                    return Err(CliError::new(format!(
+                        "Cache key {key} for --env-from-cache {reference} not found in cache {cache_name}"
+                    )))
+                }
+            };
+            from_cache.push((name, value));
+        }
+    }
+
+    Ok(merge_environment(vec![
+        from_file,
+        from_cache,
+        environment_variables,
+    ]))
+}
+
 pub async fn put_function(
-    client: FunctionClient,
+    client: &FunctionClient,
     cache_name: String,
     name: String,
     wasm_source: WasmSource,
@@ -29,6 +98,34 @@ pub async fn put_function(
     environment_variables: Vec<(String, String)>,
     metrics_change: Option<FunctionMetricsConfigChange>,
 ) -> Result<(), CliError> {
+    let mut fields = vec![
+        ("cache_name", cache_name.clone()),
+        ("name", name.clone()),
+        ("wasm_source", format_wasm_source(&wasm_source)),
+    ];
+    if let Some(description) = &description {
+        fields.push(("description", description.clone()));
+    }
+    let env_var_names: Vec<String> = environment_variables
+        .iter()
+        .map(|(key, _)| format!("environment {key}"))
+        .collect();
+    for (env_var_name, (key, value)) in env_var_names.iter().zip(&environment_variables) {
+        fields.push((
+            env_var_name.as_str(),
+            display_env_value(key, value).to_string(),
+        ));
+    }
+    if let Some(metrics_change) = &metrics_change {
+        fields.push((
+            "metrics_config",
+            format_metrics_config_change(metrics_change),
+        ));
+    }
+    if skip_sdk_request("PutFunction", &fields) {
+        return Ok(());
+    }
+
     let mut request = PutFunctionRequest::new(&cache_name, &name, wasm_source);
     if let Some(description) = description {
         request = request.description(description);
@@ -76,6 +173,29 @@ pub async fn put_function_config(
         ));
     }
 
+    let mut fields = vec![("cache_name", cache_name.clone())];
+    if let Some(name) = &function_name {
+        fields.push(("function_name", name.clone()));
+    }
+    if let Some(id) = &function_id {
+        fields.push(("function_id", id.clone()));
+    }
+    if let Some(new_version) = &new_version {
+        fields.push((
+            "current_version",
+            format_current_function_version(new_version),
+        ));
+    }
+    if let Some(metrics_change) = &metrics_change {
+        fields.push((
+            "metrics_config",
+            format_metrics_config_change(metrics_change),
+        ));
+    }
+    if skip_sdk_request("PutFunctionConfig", &fields) {
+        return Ok(());
+    }
+
     let mut request = if let Some(name) = function_name {
         PutFunctionConfigRequest::from_function_name(&cache_name, &name)
     } else if let Some(id) = function_id {
@@ -103,6 +223,128 @@ pub async fn put_function_config(
     Ok(())
 }
 
+/// Looks up a function by name, returning its ID and current version.
+async fn find_function(
+    client: &FunctionClient,
+    cache_name: &str,
+    name: &str,
+) -> Result<(String, u32), CliError> {
+    let request = ListFunctionsRequest::new(cache_name);
+    let response = client.send(request).await.map_err(Into::<CliError>::into)?;
+    let functions_list = response.into_vec().await.map_err(Into::<CliError>::into)?;
+    functions_list
+        .iter()
+        .find(|function| function.name() == name)
+        .map(|function| (function.function_id().to_string(), function.version()))
+        .ok_or_else(|| {
+            CliError::new(format!(
+                "No function named {name} in cache namespace: {cache_name}"
+            ))
+        })
+}
+
+async fn list_version_snapshots(
+    client: &FunctionClient,
+    function_id: &str,
+) -> Result<Vec<VersionSnapshot>, CliError> {
+    let request = ListFunctionVersionsRequest::new(function_id);
+    let response = client.send(request).await.map_err(Into::<CliError>::into)?;
+    let function_versions_list = response.into_vec().await.map_err(Into::<CliError>::into)?;
+    Ok(function_versions_list
+        .iter()
+        .map(|version| VersionSnapshot {
+            version: version.version_id().version(),
+            description: version.description().to_string(),
+            wasm_id: version.wasm_version_id().id().to_string(),
+            wasm_version: version.wasm_version_id().version(),
+            environment: environment_snapshot(version.environment()),
+        })
+        .collect())
+}
+
+pub async fn rollback_function(
+    client: FunctionClient,
+    cache_name: String,
+    name: String,
+    to: Option<u32>,
+) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "PutFunctionConfig",
+        &[
+            ("cache_name", cache_name.clone()),
+            ("function_name", name.clone()),
+            (
+                "current_version",
+                to.map_or(
+                    "pinned to the version before the current one".to_string(),
+                    |to| format!("pinned to {to}"),
+                ),
+            ),
+        ],
+    ) {
+        return Ok(());
+    }
+
+    let (function_id, current) = find_function(&client, &cache_name, &name).await?;
+    let versions = list_version_snapshots(&client, &function_id).await?;
+    let target = match to {
+        Some(to) => find_version(&versions, to)?.version,
+        None => previous_version(&versions, current).ok_or_else(|| {
+            CliError::new(format!(
+                "{name} is on version {current}, and there's no earlier version to roll back to"
+            ))
+        })?,
+    };
+    if target == current {
+        console_data!("{name} is already on version {current}");
+        return Ok(());
+    }
+
+    let request = PutFunctionConfigRequest::from_function_id(&cache_name, &function_id)
+        .current_version(CurrentFunctionVersion::Pinned(target));
+    let response = client.send(request).await.map_err(Into::<CliError>::into)?;
+    console_data!(
+        "{name} now runs version {} (was {current}), pinned until you run put-function-config --use-latest-version",
+        response.version()
+    );
+    if let (Ok(from), Ok(to)) = (
+        find_version(&versions, current),
+        find_version(&versions, target),
+    ) {
+        for change in diff_versions(from, to) {
+            console_data!("{change}");
+        }
+    }
+    Ok(())
+}
+
+pub async fn diff_function_versions(
+    client: FunctionClient,
+    cache_name: String,
+    name: String,
+    from: u32,
+    to: u32,
+) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "ListFunctionVersions",
+        &[
+            ("cache_name", cache_name.clone()),
+            ("function_name", name.clone()),
+        ],
+    ) {
+        return Ok(());
+    }
+    let (function_id, _) = find_function(&client, &cache_name, &name).await?;
+    let versions = list_version_snapshots(&client, &function_id).await?;
+    let changes = diff_versions(find_version(&versions, from)?, find_version(&versions, to)?);
+    if changes.is_empty() {
+        console_data!("Versions {from} and {to} of {name} are the same");
+    } else {
+        console_data!("{name} version {from} -> {to}:\n{}", changes.join("\n"));
+    }
+    Ok(())
+}
+
 pub async fn invoke_function(
     endpoint: String,
     auth_token: String,
@@ -111,12 +353,15 @@ pub async fn invoke_function(
     method: String,
     options: InvocationOptions,
 ) -> Result<(), CliError> {
-    let headers = build_invocation_headers(options.headers.unwrap_or_default().as_str())?;
-    let data = options.data.unwrap_or_default();
+    let headers = build_invocation_headers(options.headers.as_deref().unwrap_or_default())?;
+    let data = read_invocation_body(&options)?;
 
     info!("Invoking function. Name: {name}, Cache Namespace: {cache_name}");
     if !data.is_empty() {
-        info!("with payload:\n{data}");
+        match std::str::from_utf8(&data) {
+            Ok(text) => info!("with payload:\n{text}"),
+            Err(_) => info!("with a {} byte binary payload", data.len()),
+        }
     };
     if !headers.is_empty() {
         info!("with headers:\n{headers:#?}");
@@ -124,7 +369,7 @@ pub async fn invoke_function(
     info!("with request method: {method}");
 
     let full_path = build_invocation_path(cache_name, name, options.path)?;
-    let response_text = call_function_api(
+    let Some(response) = call_function_api(
         Method::from_str(&method)?,
         endpoint,
         auth_token,
@@ -132,12 +377,36 @@ pub async fn invoke_function(
         headers,
         data,
     )
-    .await?;
-    console_data!("{response_text}");
+    .await?
+    else {
+        return Ok(());
+    };
+
+    if options.include {
+        console_data!("{}\n", format_response_head(&response));
+    }
+    match &options.output_file {
+        Some(path) => fs::write(path, &response.body)
+            .map_err(|e| CliError::new(format!("Could not write --output-file {path}: {e}")))?,
+        None => match std::str::from_utf8(&response.body) {
+            Ok(text) => console_data!("{text}"),
+            Err(_) => io::stdout().write_all(&response.body).map_err(|e| {
+                CliError::new(format!("Could not write the response to stdout: {e}"))
+            })?,
+        },
+    }
+    if let Some(code) = invocation_exit_code(response.status) {
+        console_info!("The function responded with {}", response.status);
+        finish_trace();
+        exit(code);
+    }
     Ok(())
 }
 
 pub async fn list_functions(client: FunctionClient, cache_name: String) -> Result<(), CliError> {
+    if skip_sdk_request("ListFunctions", &[("cache_name", cache_name.clone())]) {
+        return Ok(());
+    }
     let request = ListFunctionsRequest::new(&cache_name);
     let response = client.send(request).await.map_err(Into::<CliError>::into)?;
     let functions_list = response.into_vec().await.map_err(Into::<CliError>::into)?;
@@ -166,9 +435,13 @@ pub async fn list_function_versions(
     client: FunctionClient,
     function_id: String,
 ) -> Result<(), CliError> {
-    let request = ListFunctionVersionsRequest::new(&function_id);
-    let response = client.send(request).await.map_err(Into::<CliError>::into)?;
-    let function_versions_list = response.into_vec().await.map_err(Into::<CliError>::into)?;
+    if skip_sdk_request(
+        "ListFunctionVersions",
+        &[("function_id", function_id.clone())],
+    ) {
+        return Ok(());
+    }
+    let function_versions_list = list_version_snapshots(&client, &function_id).await?;
 
     if function_versions_list.is_empty() {
         console_data!("No versions found for function: {function_id}");
@@ -176,12 +449,12 @@ pub async fn list_function_versions(
         console_data!("Versions for function: {function_id}");
         function_versions_list.iter().for_each(|version| {
             console_data!(
-                "\nFunction Version: {}, Description: \"{}\", Wasm ID: {}, Wasm Version: {}, Environment Variables: {:#?}",
-                version.version_id().version(),
-                version.description(),
-                version.wasm_version_id().id(),
-                version.wasm_version_id().version(),
-                version.environment()
+                "\nFunction Version: {}, Description: \"{}\", Wasm ID: {}, Wasm Version: {}, Environment Variables: {}",
+                version.version,
+                version.description,
+                version.wasm_id,
+                version.wasm_version,
+                format_environment(&version.environment)
             )
         });
     }
@@ -195,6 +468,19 @@ pub async fn put_wasm(
     description: Option<String>,
 ) -> Result<(), CliError> {
     let binary = read_wasm_file(wasm_file)?;
+    let mut fields = vec![
+        ("name", name.clone()),
+        (
+            "wasm_source",
+            format!("{} bytes of inline Wasm", binary.len()),
+        ),
+    ];
+    if let Some(description) = &description {
+        fields.push(("description", description.clone()));
+    }
+    if skip_sdk_request("PutWasm", &fields) {
+        return Ok(());
+    }
     let mut request = PutWasmRequest::new(&name, binary);
     if let Some(description) = description {
         request = request.description(description);
@@ -210,6 +496,9 @@ pub async fn put_wasm(
 }
 
 pub async fn list_wasms(client: FunctionClient) -> Result<(), CliError> {
+    if skip_sdk_request("ListWasms", &[]) {
+        return Ok(());
+    }
     let request = ListWasmsRequest::new();
     let response = client.send(request).await.map_err(Into::<CliError>::into)?;
     let wasms_list = response.into_vec().await.map_err(Into::<CliError>::into)?;
@@ -230,3 +519,136 @@ pub async fn list_wasms(client: FunctionClient) -> Result<(), CliError> {
     }
     Ok(())
 }
+
+pub async fn describe_wasm(
+    client: FunctionClient,
+    cache_name: String,
+    wasm: String,
+) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "ListWasms",
+        &[("wasm", wasm.clone()), ("cache_name", cache_name.clone())],
+    ) {
+        return Ok(());
+    }
+    let request = ListWasmsRequest::new();
+    let response = client.send(request).await.map_err(Into::<CliError>::into)?;
+    let wasms_list = response.into_vec().await.map_err(Into::<CliError>::into)?;
+    let found = wasms_list
+        .iter()
+        .find(|candidate| candidate.name() == wasm || candidate.id().id() == wasm)
+        .ok_or_else(|| CliError::new(format!("No Wasm source with name or ID {wasm}")))?;
+    let wasm_id = found.id().id().to_string();
+    console_data!(
+        "Name: {}, ID: {}, Latest Version: {}, Description: {}",
+        found.name(),
+        wasm_id,
+        found.id().version(),
+        found.description()
+    );
+
+    let functions = function_versions(&client, &cache_name).await?;
+    let references = wasm_references(&functions, &wasm_id);
+    if references.is_empty() {
+        console_data!("Not used by any function in cache namespace: {cache_name}");
+    } else {
+        console_data!(
+            "Used by functions in cache namespace {cache_name}:\n{}",
+            references.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Every version of every function in a cache namespace.
+async fn function_versions(
+    client: &FunctionClient,
+    cache_name: &str,
+) -> Result<Vec<FunctionVersions>, CliError> {
+    let request = ListFunctionsRequest::new(cache_name);
+    let response = client.send(request).await.map_err(Into::<CliError>::into)?;
+    let functions_list = response.into_vec().await.map_err(Into::<CliError>::into)?;
+    let mut functions = Vec::new();
+    for function in &functions_list {
+        functions.push(FunctionVersions {
+            name: function.name().to_string(),
+            current_version: function.version(),
+            versions: list_version_snapshots(client, function.function_id()).await?,
+        });
+    }
+    Ok(functions)
+}
+
+pub async fn prune_wasms(
+    client: FunctionClient,
+    credential_provider: CredentialProvider,
+    keep: usize,
+) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "DeleteWasm",
+        &[(
+            "name",
+            format!("every Wasm source no function version uses, keeping {keep}"),
+        )],
+    ) {
+        return Ok(());
+    }
+    let registry = FunctionRegistry::new(&credential_provider)?;
+    let request = ListWasmsRequest::new();
+    let response = client.send(request).await.map_err(Into::<CliError>::into)?;
+    let wasms: Vec<UploadedWasm> = response
+        .into_vec()
+        .await
+        .map_err(Into::<CliError>::into)?
+        .iter()
+        .map(|wasm| UploadedWasm {
+            name: wasm.name().to_string(),
+            id: wasm.id().id().to_string(),
+            latest_version: wasm.id().version(),
+        })
+        .collect();
+
+    // Functions live in cache namespaces, so a Wasm source is only unused if no namespace's
+    // functions were built from it.
+    let cache_client = get_cache_client(credential_provider).await?;
+    let caches = interact_with_momento("listing caches...", cache_client.list_caches()).await?;
+    let mut functions = Vec::new();
+    for cache in caches.caches {
+        functions.extend(function_versions(&client, &cache.name).await?);
+    }
+
+    let to_delete = plan_wasm_prune(wasms, &functions, keep);
+    if to_delete.is_empty() {
+        console_data!("No unused Wasm sources to delete");
+        return Ok(());
+    }
+    console_data!("Will delete {} unused Wasm source(s):", to_delete.len());
+    for wasm in &to_delete {
+        console_data!(
+            "  {} (ID: {}, Latest Version: {})",
+            wasm.name,
+            wasm.id,
+            wasm.latest_version
+        );
+    }
+    let mut failures = Vec::new();
+    for wasm in &to_delete {
+        if let Err(e) = registry.delete_wasm(&wasm.name).await {
+            failures.push(format!("  {}: {}", wasm.name, e.msg));
+        }
+    }
+    console_data!(
+        "{} deleted, {} failed",
+        to_delete.len() - failures.len(),
+        failures.len()
+    );
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::new(format!(
+            "{} Wasm source(s) failed:\n{}",
+            failures.len(),
+            failures.join("\n")
+        )))
+    }
+}
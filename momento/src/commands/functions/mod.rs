@@ -1,2 +1,4 @@
+pub mod deploy;
 pub mod function_cli;
+pub mod registry;
 pub mod utils;
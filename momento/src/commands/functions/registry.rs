@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use momento::{CredentialProvider, MomentoError};
+use momento_protos::function::{
+    function_registry_client::FunctionRegistryClient, DeleteWasmRequest,
+};
+use tonic::{
+    metadata::AsciiMetadataValue,
+    service::{interceptor::InterceptedService, Interceptor},
+    transport::{Channel, ClientTlsConfig},
+    Request, Status,
+};
+
+use crate::error::CliError;
+
+/// The Function registry's gRPC client, for the calls the SDK's `FunctionClient` doesn't
+/// offer yet, like DeleteWasm.
+pub struct FunctionRegistry {
+    client: FunctionRegistryClient<InterceptedService<Channel, Authorization>>,
+}
+
+/// Sends the API key with each request, as the SDK does.
+#[derive(Clone)]
+pub struct Authorization(AsciiMetadataValue);
+
+impl Interceptor for Authorization {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert("authorization", self.0.clone());
+        Ok(request)
+    }
+}
+
+impl FunctionRegistry {
+    pub fn new(credential_provider: &CredentialProvider) -> Result<Self, CliError> {
+        let endpoint = registry_endpoint(credential_provider.cache_http_endpoint());
+        let mut channel = Channel::from_shared(endpoint.clone())
+            .map_err(|e| CliError::new(format!("Invalid Functions endpoint {endpoint}: {e}")))?;
+        if endpoint.starts_with("https://") {
+            channel = channel
+                .tls_config(ClientTlsConfig::new().with_webpki_roots())
+                .map_err(|e| CliError::new(format!("Could not set up TLS: {e}")))?;
+        }
+        let authorization = AsciiMetadataValue::try_from(credential_provider.auth_token())
+            .map_err(|e| CliError::new(format!("Invalid API key: {e}")))?;
+        Ok(Self {
+            client: FunctionRegistryClient::with_interceptor(
+                channel.connect_lazy(),
+                Authorization(authorization),
+            ),
+        })
+    }
+
+    /// Deletes a Wasm source, every version of it.
+    pub async fn delete_wasm(&self, name: &str) -> Result<(), CliError> {
+        let mut request = Request::new(DeleteWasmRequest {
+            name: name.to_string(),
+        });
+        request.set_timeout(Duration::from_secs(15));
+        self.client
+            .clone()
+            .delete_wasm(request)
+            .await
+            .map_err(|status| CliError::from(MomentoError::from(status)))?;
+        Ok(())
+    }
+}
+
+/// Functions are served from the cache endpoint, which is the HTTP API's without the `api.`.
+fn registry_endpoint(cache_http_endpoint: &str) -> String {
+    cache_http_endpoint.replacen("://api.cache.", "://cache.", 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_on_the_cache_endpoint() {
+        assert_eq!(
+            registry_endpoint("https://api.cache.cell-us-east-1-1.prod.a.momentohq.com"),
+            "https://cache.cell-us-east-1-1.prod.a.momentohq.com"
+        );
+        assert_eq!(
+            registry_endpoint("http://localhost:9001"),
+            "http://localhost:9001"
+        );
+    }
+}
@@ -1,15 +1,20 @@
-use crate::commands::utils::{call_momento_http_api_raw, MomentoHttpData};
+use crate::commands::utils::{
+    call_momento_http_api_full, MomentoHttpData, MomentoHttpFullResponse,
+};
 use crate::error::CliError;
+use crate::utils::dry_run::{is_secret_name, REDACTED};
 
 use momento::functions::{
-    CurrentFunctionVersion, FunctionMetricsConfig, FunctionMetricsConfigChange, WasmSource,
+    CurrentFunctionVersion, EnvironmentValue, FunctionMetricsConfig, FunctionMetricsConfigChange,
+    WasmSource,
 };
 
 use form_urlencoded;
-use http::{method::InvalidMethod, Method};
+use http::{method::InvalidMethod, Method, StatusCode};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue, InvalidHeaderName, InvalidHeaderValue};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::io::{self, Read};
 use std::str::FromStr; // to use HeaderName::from_str
 
 /// put-function
@@ -23,6 +28,26 @@ pub fn read_wasm_file(wasm_file: String) -> Result<Vec<u8>, CliError> {
     Ok(binary)
 }
 
+const WASM_MAGIC: &[u8] = b"\0asm";
+const CORE_MODULE_VERSION: &[u8] = &[0x01, 0x00, 0x00, 0x00];
+const COMPONENT_VERSION: &[u8] = &[0x0d, 0x00, 0x01, 0x00];
+
+/// Checks the preamble of a Wasm binary: Momento Functions run components, not core modules.
+pub fn validate_wasm_component(binary: &[u8]) -> Result<(), CliError> {
+    if binary.len() < 8 || &binary[..4] != WASM_MAGIC {
+        return Err(CliError::new("Not a Wasm binary"));
+    }
+    match &binary[4..8] {
+        version if version == COMPONENT_VERSION => Ok(()),
+        version if version == CORE_MODULE_VERSION => Err(CliError::new(
+            "This is a core Wasm module, not a component. Build it with --target wasm32-wasip2",
+        )),
+        version => Err(CliError::new(format!(
+            "Unrecognized Wasm version {version:02x?}"
+        ))),
+    }
+}
+
 pub fn determine_wasm_source(
     wasm_file: Option<String>,
     id_uploaded_wasm: Option<String>,
@@ -85,11 +110,372 @@ pub fn format_metrics_config(metrics_config: Option<&FunctionMetricsConfig>) ->
     }
 }
 
+/// Renders a metrics configuration change for display, as `--dry-run` does.
+pub fn format_metrics_config_change(metrics_change: &FunctionMetricsConfigChange) -> String {
+    match metrics_change {
+        FunctionMetricsConfigChange::Remove => "remove (follow account-wide default)".to_string(),
+        FunctionMetricsConfigChange::Set(metrics_config) => {
+            format_metrics_config(Some(metrics_config))
+        }
+    }
+}
+
+pub fn format_current_function_version(version: &CurrentFunctionVersion) -> String {
+    match version {
+        CurrentFunctionVersion::Latest => "latest".to_string(),
+        CurrentFunctionVersion::Pinned(version) => format!("pinned to {version}"),
+    }
+}
+
+pub fn format_wasm_source(wasm_source: &WasmSource) -> String {
+    match wasm_source {
+        WasmSource::Inline(binary) => format!("{} bytes of inline Wasm", binary.len()),
+        WasmSource::Reference { wasm_id, version } => {
+            format!("uploaded Wasm {wasm_id}, version {version}")
+        }
+    }
+}
+
+/// put-function / deploy
+///
+/// Reads `KEY=VALUE` lines from a dotenv file. Lines may start with `export`, `#` starts a
+/// comment, single-quoted values are taken literally, and double-quoted values understand
+/// `\n`, `\t`, and backslash escapes. `${VAR}` in unquoted and double-quoted values is replaced
+/// by `lookup(VAR)`, and it's an error if that's unset.
+pub fn parse_env_file(
+    contents: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<(String, String)>, CliError> {
+    let mut variables = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line
+            .strip_prefix("export ")
+            .map(str::trim_start)
+            .unwrap_or(line);
+        let parsed = line
+            .split_once('=')
+            .ok_or_else(|| "expected KEY=VALUE".to_string())
+            .and_then(|(key, value)| {
+                let key = key.trim();
+                if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(format!("{key:?} isn't a valid variable name"));
+                }
+                Ok((
+                    key.to_string(),
+                    parse_env_value(value.trim_start(), &lookup)?,
+                ))
+            });
+        variables.push(parsed.map_err(|e| CliError::new(format!("line {}: {e}", index + 1)))?);
+    }
+    Ok(variables)
+}
+
+fn parse_env_value(raw: &str, lookup: &impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    if let Some(rest) = raw.strip_prefix('\'') {
+        let end = rest.find('\'').ok_or("unterminated single quote")?;
+        check_after_quote(&rest[end + 1..])?;
+        return Ok(rest[..end].to_string());
+    }
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut chars = rest.chars();
+        let value = expand_value(&mut chars, true, lookup)?;
+        check_after_quote(chars.as_str())?;
+        return Ok(value);
+    }
+    let unquoted = raw.split(" #").next().unwrap_or_default().trim_end();
+    expand_value(&mut unquoted.chars(), false, lookup)
+}
+
+fn check_after_quote(rest: &str) -> Result<(), String> {
+    let rest = rest.trim();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("unexpected {rest:?} after the closing quote"))
+    }
+}
+
+/// Expands `${VAR}` references, and for a double-quoted value, reads escapes up to the
+/// closing quote.
+fn expand_value(
+    chars: &mut std::str::Chars,
+    quoted: bool,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut value = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => return Ok(value),
+            '\\' if quoted => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(c) => value.push(c),
+                None => break,
+            },
+            '$' if chars.as_str().starts_with('{') => {
+                let rest = &chars.as_str()[1..];
+                let end = rest.find('}').ok_or("unterminated ${")?;
+                let name = &rest[..end];
+                let expanded = lookup(name)
+                    .ok_or_else(|| format!("${{{name}}} isn't set in your environment"))?;
+                value.push_str(&expanded);
+                *chars = rest[end + 1..].chars();
+            }
+            c => value.push(c),
+        }
+    }
+    if quoted {
+        Err("unterminated double quote".to_string())
+    } else {
+        Ok(value)
+    }
+}
+
+/// Splits an `--env-from-cache` reference into the variable name and the cache key. A bare
+/// `KEY` names both.
+pub fn parse_cache_reference(reference: &str) -> (String, String) {
+    match reference.split_once('=') {
+        Some((name, key)) => (name.to_string(), key.to_string()),
+        None => (reference.to_string(), reference.to_string()),
+    }
+}
+
+/// Combines environment variables from each source in turn; later sources override earlier
+/// ones, and each name keeps the position where it first appeared.
+pub fn merge_environment(sources: Vec<Vec<(String, String)>>) -> Vec<(String, String)> {
+    let mut merged: Vec<(String, String)> = Vec::new();
+    for (key, value) in sources.into_iter().flatten() {
+        match merged.iter_mut().find(|(existing, _)| *existing == key) {
+            Some(entry) => entry.1 = value,
+            None => merged.push((key, value)),
+        }
+    }
+    merged
+}
+
+/// Hides values whose names look secret, by the same rules `--dry-run` uses.
+pub fn display_env_value<'a>(name: &str, value: &'a str) -> &'a str {
+    if is_secret_name(name) {
+        REDACTED
+    } else {
+        value
+    }
+}
+
+pub fn format_environment(environment: &BTreeMap<String, String>) -> String {
+    if environment.is_empty() {
+        return "none".to_string();
+    }
+    environment
+        .iter()
+        .map(|(key, value)| format!("{key}={}", display_env_value(key, value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// rollback / diff
+///
+/// The parts of a function version that a deploy can change, with environment values rendered
+/// for display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionSnapshot {
+    pub version: u32,
+    pub description: String,
+    pub wasm_id: String,
+    pub wasm_version: u32,
+    pub environment: BTreeMap<String, String>,
+}
+
+/// A version's environment as plain strings, for display and comparison.
+pub fn environment_snapshot(
+    environment: &HashMap<String, EnvironmentValue>,
+) -> BTreeMap<String, String> {
+    environment
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                EnvironmentValue::Literal(value) => value.clone(),
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+/// The version `rollback --previous` goes back to: the newest one older than `current`.
+pub fn previous_version(versions: &[VersionSnapshot], current: u32) -> Option<u32> {
+    versions
+        .iter()
+        .map(|snapshot| snapshot.version)
+        .filter(|version| *version < current)
+        .max()
+}
+
+pub fn find_version(
+    versions: &[VersionSnapshot],
+    version: u32,
+) -> Result<&VersionSnapshot, CliError> {
+    versions
+        .iter()
+        .find(|snapshot| snapshot.version == version)
+        .ok_or_else(|| {
+            CliError::new(format!(
+                "Version {version} not found. Available versions: {}",
+                versions
+                    .iter()
+                    .map(|snapshot| snapshot.version.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+}
+
+/// One line per change between two versions; empty if they're the same.
+pub fn diff_versions(from: &VersionSnapshot, to: &VersionSnapshot) -> Vec<String> {
+    let mut changes = Vec::new();
+    if (&from.wasm_id, from.wasm_version) != (&to.wasm_id, to.wasm_version) {
+        changes.push(format!(
+            "  wasm: {} version {} -> {} version {}",
+            from.wasm_id, from.wasm_version, to.wasm_id, to.wasm_version
+        ));
+    }
+    if from.description != to.description {
+        changes.push(format!(
+            "  description: {:?} -> {:?}",
+            from.description, to.description
+        ));
+    }
+    for (key, value) in &from.environment {
+        match to.environment.get(key) {
+            None => changes.push(format!("- env {key}: {}", display_env_value(key, value))),
+            Some(new_value) if new_value != value => changes.push(format!(
+                "~ env {key}: {} -> {}",
+                display_env_value(key, value),
+                display_env_value(key, new_value)
+            )),
+            Some(_) => {}
+        }
+    }
+    for (key, value) in &to.environment {
+        if !from.environment.contains_key(key) {
+            changes.push(format!("+ env {key}: {}", display_env_value(key, value)));
+        }
+    }
+    changes
+}
+
+/// describe-wasm
+pub struct FunctionVersions {
+    pub name: String,
+    pub current_version: u32,
+    pub versions: Vec<VersionSnapshot>,
+}
+
+/// One line per function version built from the Wasm source `wasm_id`.
+pub fn wasm_references(functions: &[FunctionVersions], wasm_id: &str) -> Vec<String> {
+    let mut references = Vec::new();
+    for function in functions {
+        for snapshot in &function.versions {
+            if snapshot.wasm_id == wasm_id {
+                references.push(format!(
+                    "  {} version {}{}: Wasm version {}",
+                    function.name,
+                    snapshot.version,
+                    if snapshot.version == function.current_version {
+                        " (current)"
+                    } else {
+                        ""
+                    },
+                    snapshot.wasm_version
+                ));
+            }
+        }
+    }
+    references
+}
+
+/// A Wasm source as ListWasms reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedWasm {
+    pub name: String,
+    pub id: String,
+    pub latest_version: u32,
+}
+
+/// The Wasm sources prune-wasms deletes: those no function version was built from, less the
+/// `keep` with the most versions.
+pub fn plan_wasm_prune(
+    wasms: Vec<UploadedWasm>,
+    functions: &[FunctionVersions],
+    keep: usize,
+) -> Vec<UploadedWasm> {
+    let mut unused: Vec<UploadedWasm> = wasms
+        .into_iter()
+        .filter(|wasm| wasm_references(functions, &wasm.id).is_empty())
+        .collect();
+    unused.sort_by(|a, b| {
+        b.latest_version
+            .cmp(&a.latest_version)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    unused.split_off(keep.min(unused.len()))
+}
+
 /// invoke-function
 pub struct InvocationOptions {
     pub data: Option<String>,
+    pub data_file: Option<String>,
+    pub data_stdin: bool,
     pub headers: Option<String>,
     pub path: Option<String>,
+    pub include: bool,
+    pub output_file: Option<String>,
+}
+
+/// The request body, from whichever of `--data`, `--data-file`, or `--data-stdin` was given.
+/// Files and stdin are sent byte for byte.
+pub fn read_invocation_body(options: &InvocationOptions) -> Result<Vec<u8>, CliError> {
+    if let Some(path) = &options.data_file {
+        return fs::read(path)
+            .map_err(|e| CliError::new(format!("Could not read --data-file {path}: {e}")));
+    }
+    if options.data_stdin {
+        let mut data = Vec::new();
+        io::stdin()
+            .lock()
+            .read_to_end(&mut data)
+            .map_err(|e| CliError::new(format!("Could not read the body from stdin: {e}")))?;
+        return Ok(data);
+    }
+    Ok(options.data.clone().unwrap_or_default().into_bytes())
+}
+
+/// The status line and headers of a response, as `curl --include` shows them.
+pub fn format_response_head(response: &MomentoHttpFullResponse) -> String {
+    let mut head = format!("HTTP {}", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!(
+            "\n{name}: {}",
+            String::from_utf8_lossy(value.as_bytes())
+        ));
+    }
+    head
+}
+
+/// Exit status for a function's non-2xx response: 4 for 4xx, 5 for 5xx, and 3 for anything else.
+pub fn invocation_exit_code(status: StatusCode) -> Option<i32> {
+    if status.is_success() {
+        None
+    } else if status.is_client_error() {
+        Some(4)
+    } else if status.is_server_error() {
+        Some(5)
+    } else {
+        Some(3)
+    }
 }
 
 pub fn build_invocation_headers(headers_str: &str) -> Result<HeaderMap, CliError> {
@@ -157,14 +543,14 @@ pub async fn call_function_api(
     auth_token: String,
     full_path: String,
     headers: reqwest::header::HeaderMap,
-    data: String,
-) -> Result<String, CliError> {
-    call_momento_http_api_raw(
+    data: Vec<u8>,
+) -> Result<Option<MomentoHttpFullResponse>, CliError> {
+    call_momento_http_api_full(
         method,
         format!("{endpoint}/functions/{full_path}"),
         auth_token,
         Some(headers),
-        Some(MomentoHttpData::String(data)),
+        Some(MomentoHttpData::Bytes(data)),
     )
     .await
 }
@@ -188,3 +574,287 @@ impl From<InvalidHeaderValue> for CliError {
             .with_details(format!("reqwest {e:#?} error while parsing headers: {e}"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_components_pass_validation() {
+        let component = [b"\0asm".as_slice(), &[0x0d, 0x00, 0x01, 0x00, 0x01]].concat();
+        assert!(validate_wasm_component(&component).is_ok());
+
+        let module = [b"\0asm".as_slice(), &[0x01, 0x00, 0x00, 0x00]].concat();
+        assert!(validate_wasm_component(&module)
+            .unwrap_err()
+            .msg
+            .contains("core Wasm module"));
+        assert_eq!(
+            validate_wasm_component(b"\0asm").unwrap_err().msg,
+            "Not a Wasm binary"
+        );
+        assert_eq!(
+            validate_wasm_component(b"#!/bin/sh\n").unwrap_err().msg,
+            "Not a Wasm binary"
+        );
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        (name == "HOME").then(|| "/home/me".to_string())
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn env_file_handles_comments_quotes_and_expansion() {
+        let contents = r#"
+# settings for local runs
+export MODE=fast # inline comment
+GREETING="hello\n\"world\""
+LITERAL='${HOME} stays'
+CONFIG_DIR=${HOME}/.config
+QUOTED_DIR="${HOME}/data" # trailing comment
+EMPTY=
+"#;
+        assert_eq!(
+            parse_env_file(contents, lookup).expect("should parse"),
+            pairs(&[
+                ("MODE", "fast"),
+                ("GREETING", "hello\n\"world\""),
+                ("LITERAL", "${HOME} stays"),
+                ("CONFIG_DIR", "/home/me/.config"),
+                ("QUOTED_DIR", "/home/me/data"),
+                ("EMPTY", ""),
+            ])
+        );
+    }
+
+    #[test]
+    fn env_file_errors_name_the_line() {
+        assert_eq!(
+            parse_env_file("A=1\nB=${UNSET}", lookup).unwrap_err().msg,
+            "line 2: ${UNSET} isn't set in your environment"
+        );
+        assert_eq!(
+            parse_env_file("A=\"open", lookup).unwrap_err().msg,
+            "line 1: unterminated double quote"
+        );
+        assert_eq!(
+            parse_env_file("just words", lookup).unwrap_err().msg,
+            "line 1: expected KEY=VALUE"
+        );
+        assert_eq!(
+            parse_env_file("A='x' y", lookup).unwrap_err().msg,
+            "line 1: unexpected \"y\" after the closing quote"
+        );
+    }
+
+    #[test]
+    fn later_environment_sources_win() {
+        assert_eq!(
+            merge_environment(vec![
+                pairs(&[("A", "file"), ("B", "file")]),
+                pairs(&[("B", "cache")]),
+                pairs(&[("A", "flag"), ("C", "flag")]),
+            ]),
+            pairs(&[("A", "flag"), ("B", "cache"), ("C", "flag")])
+        );
+        assert_eq!(
+            parse_cache_reference("DB_URL=prod/db-url"),
+            ("DB_URL".to_string(), "prod/db-url".to_string())
+        );
+        assert_eq!(
+            parse_cache_reference("DB_URL"),
+            ("DB_URL".to_string(), "DB_URL".to_string())
+        );
+    }
+
+    #[test]
+    fn secret_looking_values_are_redacted() {
+        let environment = BTreeMap::from([
+            ("GITHUB_TOKEN".to_string(), "ghp_123".to_string()),
+            ("db_password".to_string(), "hunter2".to_string()),
+            ("SIGNING_KEY".to_string(), "abc123".to_string()),
+            ("AUTH_MODE".to_string(), "oidc".to_string()),
+            ("MODE".to_string(), "fast".to_string()),
+        ]);
+        assert_eq!(
+            format_environment(&environment),
+            "AUTH_MODE=oidc, GITHUB_TOKEN=<redacted>, MODE=fast, SIGNING_KEY=<redacted>, \
+             db_password=<redacted>"
+        );
+        assert_eq!(format_environment(&BTreeMap::new()), "none");
+    }
+
+    fn snapshot(version: u32, environment: &[(&str, &str)]) -> VersionSnapshot {
+        VersionSnapshot {
+            version,
+            description: "echo".to_string(),
+            wasm_id: "wasm-1".to_string(),
+            wasm_version: version,
+            environment: environment_snapshot(
+                &environment
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), EnvironmentValue::from(*value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn environment_values_are_shown_as_their_text() {
+        let environment = HashMap::from([
+            (
+                "MODE".to_string(),
+                EnvironmentValue::Literal("fast".to_string()),
+            ),
+            (
+                "EMPTY".to_string(),
+                EnvironmentValue::Literal(String::new()),
+            ),
+        ]);
+        let snapshot = environment_snapshot(&environment);
+        assert_eq!(format_environment(&snapshot), "EMPTY=, MODE=fast");
+    }
+
+    #[test]
+    fn previous_version_skips_gaps() {
+        let versions = [snapshot(1, &[]), snapshot(2, &[]), snapshot(5, &[])];
+        assert_eq!(previous_version(&versions, 5), Some(2));
+        assert_eq!(previous_version(&versions, 1), None);
+        assert_eq!(
+            find_version(&versions, 3).unwrap_err().msg,
+            "Version 3 not found. Available versions: 1, 2, 5"
+        );
+    }
+
+    #[test]
+    fn diff_lists_wasm_description_and_environment_changes() {
+        let from = snapshot(1, &[("MODE", "slow"), ("OLD", "1")]);
+        let mut to = snapshot(2, &[("MODE", "fast"), ("NEW", "2")]);
+        to.description = "echo v2".to_string();
+        assert_eq!(
+            diff_versions(&from, &to),
+            vec![
+                "  wasm: wasm-1 version 1 -> wasm-1 version 2",
+                "  description: \"echo\" -> \"echo v2\"",
+                "~ env MODE: slow -> fast",
+                "- env OLD: 1",
+                "+ env NEW: 2",
+            ]
+        );
+        assert!(diff_versions(&from, &from).is_empty());
+    }
+
+    #[test]
+    fn wasm_references_mark_current_versions() {
+        let mut other_wasm = snapshot(3, &[]);
+        other_wasm.wasm_id = "wasm-2".to_string();
+        let functions = [
+            FunctionVersions {
+                name: "echo".to_string(),
+                current_version: 2,
+                versions: vec![snapshot(1, &[]), snapshot(2, &[]), other_wasm],
+            },
+            FunctionVersions {
+                name: "unrelated".to_string(),
+                current_version: 1,
+                versions: vec![],
+            },
+        ];
+        assert_eq!(
+            wasm_references(&functions, "wasm-1"),
+            vec![
+                "  echo version 1: Wasm version 1",
+                "  echo version 2 (current): Wasm version 2",
+            ]
+        );
+        assert!(wasm_references(&functions, "wasm-3").is_empty());
+    }
+
+    #[test]
+    fn prune_spares_used_wasms_and_the_ones_to_keep() {
+        let wasm = |name: &str, id: &str, latest_version| UploadedWasm {
+            name: name.to_string(),
+            id: id.to_string(),
+            latest_version,
+        };
+        let mut old_version = snapshot(1, &[]);
+        old_version.wasm_id = "wasm-old".to_string();
+        let functions = [FunctionVersions {
+            name: "echo".to_string(),
+            current_version: 2,
+            versions: vec![old_version, snapshot(2, &[])],
+        }];
+        let wasms = vec![
+            wasm("echo", "wasm-1", 2),
+            wasm("echo-old", "wasm-old", 1),
+            wasm("scratch", "wasm-2", 1),
+            wasm("busy", "wasm-3", 7),
+            wasm("abandoned", "wasm-4", 1),
+        ];
+
+        assert_eq!(
+            plan_wasm_prune(wasms.clone(), &functions, 0),
+            vec![
+                wasm("busy", "wasm-3", 7),
+                wasm("abandoned", "wasm-4", 1),
+                wasm("scratch", "wasm-2", 1),
+            ]
+        );
+        assert_eq!(
+            plan_wasm_prune(wasms.clone(), &functions, 1),
+            vec![wasm("abandoned", "wasm-4", 1), wasm("scratch", "wasm-2", 1)]
+        );
+        assert!(plan_wasm_prune(wasms, &functions, 5).is_empty());
+    }
+
+    #[test]
+    fn non_2xx_statuses_have_exit_codes() {
+        assert_eq!(invocation_exit_code(StatusCode::OK), None);
+        assert_eq!(invocation_exit_code(StatusCode::NO_CONTENT), None);
+        assert_eq!(invocation_exit_code(StatusCode::MOVED_PERMANENTLY), Some(3));
+        assert_eq!(invocation_exit_code(StatusCode::NOT_FOUND), Some(4));
+        assert_eq!(invocation_exit_code(StatusCode::BAD_GATEWAY), Some(5));
+    }
+
+    #[test]
+    fn response_head_has_status_and_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", HeaderValue::from_static("text/plain"));
+        let response = MomentoHttpFullResponse {
+            status: StatusCode::NOT_FOUND,
+            headers,
+            body: vec![],
+        };
+        assert_eq!(
+            format_response_head(&response),
+            "HTTP 404 Not Found\ncontent-type: text/plain"
+        );
+    }
+
+    #[test]
+    fn body_from_data_file_is_binary_safe() {
+        let dir = tempdir::TempDir::new("invoke").expect("should create temp dir");
+        let path = dir.path().join("body.bin");
+        fs::write(&path, [0xff, 0x00, 0xfe]).expect("should write body");
+        let options = InvocationOptions {
+            data: None,
+            data_file: Some(path.to_string_lossy().to_string()),
+            data_stdin: false,
+            headers: None,
+            path: None,
+            include: false,
+            output_file: None,
+        };
+        assert_eq!(
+            read_invocation_body(&options).expect("should read body"),
+            vec![0xff, 0x00, 0xfe]
+        );
+    }
+}
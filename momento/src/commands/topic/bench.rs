@@ -0,0 +1,429 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU32,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::StreamExt;
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use momento::{
+    topics::{Subscription, ValueKind},
+    TopicClient,
+};
+use momento_cli_opts::OutputFormat;
+use serde::Serialize;
+use tokio::{task::JoinSet, time::Instant};
+
+use crate::{
+    error::CliError,
+    utils::{
+        console::{console_data, console_info},
+        dry_run::skip_sdk_request,
+        latency::LatencyPercentiles,
+    },
+};
+
+/// How long subscribers keep listening after the last publish, for messages still in flight.
+const DRAIN_TIME: Duration = Duration::from_secs(2);
+
+pub struct TopicBenchOptions {
+    pub cache_name: String,
+    pub topic: String,
+    pub subscribers: u32,
+    pub publishers: u32,
+    pub rate: u32,
+    pub duration: Duration,
+    pub output: OutputFormat,
+}
+
+#[derive(Debug, Default)]
+struct PublisherStats {
+    /// Messages published successfully; also the next sequence number.
+    published: u64,
+    errors: BTreeMap<String, u64>,
+}
+
+/// What one subscriber saw. Latencies are in microseconds.
+#[derive(Debug, Default)]
+struct SubscriberStats {
+    latencies: Vec<u64>,
+    received: u64,
+    /// The sequence number each publisher should send next, as far as this subscriber knows.
+    next_sequences: HashMap<u32, u64>,
+    /// Messages skipped over by a later sequence number, less any that showed up late.
+    gaps: u64,
+    out_of_order: u64,
+    unrecognized: u64,
+}
+
+impl SubscriberStats {
+    fn record(&mut self, publisher: u32, sequence: u64, latency: u64) {
+        self.received += 1;
+        self.latencies.push(latency);
+        let next_sequence = self.next_sequences.entry(publisher).or_default();
+        if sequence >= *next_sequence {
+            self.gaps += sequence - *next_sequence;
+            *next_sequence = sequence + 1;
+        } else {
+            self.out_of_order += 1;
+            self.gaps = self.gaps.saturating_sub(1);
+        }
+    }
+
+    /// Messages this subscriber never got, given how many each publisher sent.
+    fn lost(&self, published: &[u64]) -> u64 {
+        let never_reached: u64 = published
+            .iter()
+            .enumerate()
+            .map(|(publisher, published)| {
+                let next_sequence = self
+                    .next_sequences
+                    .get(&(publisher as u32))
+                    .copied()
+                    .unwrap_or_default();
+                published.saturating_sub(next_sequence)
+            })
+            .sum();
+        self.gaps + never_reached
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TopicBenchReport {
+    pub cache_name: String,
+    pub topic: String,
+    pub publishers: u32,
+    pub subscribers: u32,
+    pub target_rate_per_second: u32,
+    pub elapsed_seconds: f64,
+    pub published: u64,
+    pub publish_rate_per_second: f64,
+    /// Failed publishes, by `MomentoErrorCode`.
+    pub publish_errors: BTreeMap<String, u64>,
+    /// Messages received, summed over all subscribers.
+    pub delivered: u64,
+    /// Messages some subscriber never received, summed over all subscribers.
+    pub lost: u64,
+    pub loss_percent: f64,
+    pub out_of_order: u64,
+    pub unrecognized: u64,
+    pub latency_ms: Option<LatencyPercentiles>,
+}
+
+fn format_payload(publisher: u32, sequence: u64, sent_at: u64) -> String {
+    format!("{publisher}:{sequence}:{sent_at}")
+}
+
+fn parse_payload(payload: &str) -> Option<(u32, u64, u64)> {
+    let mut parts = payload.splitn(3, ':');
+    let publisher = parts.next()?.parse().ok()?;
+    let sequence = parts.next()?.parse().ok()?;
+    let sent_at = parts.next()?.parse().ok()?;
+    Some((publisher, sequence, sent_at))
+}
+
+fn micros_since_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+pub async fn bench(client: TopicClient, options: TopicBenchOptions) -> Result<(), CliError> {
+    if skip_sdk_request(
+        "TopicBench",
+        &[
+            ("cache_name", options.cache_name.clone()),
+            ("topic", options.topic.clone()),
+            ("subscribers", options.subscribers.to_string()),
+            ("publishers", options.publishers.to_string()),
+            ("rate", options.rate.to_string()),
+            ("duration", format!("{:?}", options.duration)),
+        ],
+    ) {
+        return Ok(());
+    }
+
+    // Everyone subscribes before anything is published, so every message can be accounted for.
+    let mut subscriptions = Vec::new();
+    for _ in 0..options.subscribers {
+        let subscription = client
+            .subscribe(options.cache_name.as_str(), options.topic.as_str())
+            .await?;
+        subscriptions.push(subscription);
+    }
+
+    console_info!(
+        "Benchmarking topic {} in cache {}: {} publishers at {} messages/s to {} subscribers for {:?}...",
+        options.topic,
+        options.cache_name,
+        options.publishers,
+        options.rate,
+        options.subscribers,
+        options.duration
+    );
+    let started = Instant::now();
+    let deadline = started + options.duration;
+
+    let mut subscribers = JoinSet::new();
+    for subscription in subscriptions {
+        subscribers.spawn(run_subscriber(subscription, deadline + DRAIN_TIME));
+    }
+
+    let limiter = Arc::new(RateLimiter::direct(Quota::per_second(
+        NonZeroU32::new(options.rate).unwrap_or(NonZeroU32::MIN),
+    )));
+    let cache_name = Arc::new(options.cache_name.clone());
+    let topic = Arc::new(options.topic.clone());
+    let mut publishers = JoinSet::new();
+    for publisher in 0..options.publishers {
+        let (client, cache_name, topic, limiter) = (
+            client.clone(),
+            cache_name.clone(),
+            topic.clone(),
+            limiter.clone(),
+        );
+        publishers.spawn(async move {
+            let stats =
+                run_publisher(client, cache_name, topic, publisher, limiter, deadline).await;
+            (publisher, stats)
+        });
+    }
+
+    let mut published = vec![0; options.publishers as usize];
+    let mut publish_errors = BTreeMap::new();
+    while let Some(result) = publishers.join_next().await {
+        let (publisher, stats) =
+            result.map_err(|e| CliError::new(format!("A benchmark publisher failed: {e}")))?;
+        published[publisher as usize] = stats.published;
+        for (code, count) in stats.errors {
+            *publish_errors.entry(code).or_default() += count;
+        }
+    }
+    let elapsed = started.elapsed();
+
+    let mut subscriber_stats = Vec::new();
+    while let Some(result) = subscribers.join_next().await {
+        subscriber_stats.push(
+            result.map_err(|e| CliError::new(format!("A benchmark subscriber failed: {e}")))?,
+        );
+    }
+
+    let report = summarize(
+        &options,
+        elapsed,
+        &published,
+        publish_errors,
+        subscriber_stats,
+    );
+    match options.output {
+        OutputFormat::Text => console_data!("{}", format_report(&report)),
+        OutputFormat::Json => console_data!(
+            "{}",
+            serde_json::to_string_pretty(&report)
+                .map_err(|e| CliError::new(format!("Could not serialize the report: {e}")))?
+        ),
+    }
+    Ok(())
+}
+
+async fn run_publisher(
+    client: TopicClient,
+    cache_name: Arc<String>,
+    topic: Arc<String>,
+    publisher: u32,
+    limiter: Arc<DefaultDirectRateLimiter>,
+    deadline: Instant,
+) -> PublisherStats {
+    let mut stats = PublisherStats::default();
+    while Instant::now() < deadline {
+        limiter.until_ready().await;
+        let payload = format_payload(publisher, stats.published, micros_since_epoch());
+        match client
+            .publish(cache_name.as_str(), topic.as_str(), payload)
+            .await
+        {
+            Ok(_) => stats.published += 1,
+            Err(e) => {
+                *stats
+                    .errors
+                    .entry(format!("{:?}", e.error_code))
+                    .or_default() += 1;
+            }
+        }
+    }
+    stats
+}
+
+async fn run_subscriber(mut subscription: Subscription, stop: Instant) -> SubscriberStats {
+    let mut stats = SubscriberStats::default();
+    while let Ok(Some(item)) = tokio::time::timeout_at(stop, subscription.next()).await {
+        let received_at = micros_since_epoch();
+        match item.kind {
+            ValueKind::Text(text) => match parse_payload(&text) {
+                Some((publisher, sequence, sent_at)) => {
+                    stats.record(publisher, sequence, received_at.saturating_sub(sent_at))
+                }
+                None => stats.unrecognized += 1,
+            },
+            ValueKind::Binary(_) => stats.unrecognized += 1,
+        }
+    }
+    stats
+}
+
+fn summarize(
+    options: &TopicBenchOptions,
+    elapsed: Duration,
+    published: &[u64],
+    publish_errors: BTreeMap<String, u64>,
+    subscriber_stats: Vec<SubscriberStats>,
+) -> TopicBenchReport {
+    let total_published: u64 = published.iter().sum();
+    let expected_deliveries = total_published * subscriber_stats.len() as u64;
+    let lost: u64 = subscriber_stats
+        .iter()
+        .map(|stats| stats.lost(published))
+        .sum();
+    let mut latencies = Vec::new();
+    let (mut delivered, mut out_of_order, mut unrecognized) = (0, 0, 0);
+    for stats in subscriber_stats {
+        delivered += stats.received;
+        out_of_order += stats.out_of_order;
+        unrecognized += stats.unrecognized;
+        latencies.extend(stats.latencies);
+    }
+    TopicBenchReport {
+        cache_name: options.cache_name.clone(),
+        topic: options.topic.clone(),
+        publishers: options.publishers,
+        subscribers: options.subscribers,
+        target_rate_per_second: options.rate,
+        elapsed_seconds: elapsed.as_secs_f64(),
+        published: total_published,
+        publish_rate_per_second: total_published as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        publish_errors,
+        delivered,
+        lost,
+        loss_percent: if expected_deliveries == 0 {
+            0.0
+        } else {
+            lost as f64 * 100.0 / expected_deliveries as f64
+        },
+        out_of_order,
+        unrecognized,
+        latency_ms: LatencyPercentiles::from_micros(latencies),
+    }
+}
+
+fn format_report(report: &TopicBenchReport) -> String {
+    let mut text = format!(
+        "Topic {} in cache {}: {} publishers, {} subscribers, {:.1}s\n\
+        Published: {} ({:.1}/s, target {}/s)\n\
+        Delivered: {} (summed over subscribers)\n\
+        Lost: {} ({:.2}%), out of order: {}",
+        report.topic,
+        report.cache_name,
+        report.publishers,
+        report.subscribers,
+        report.elapsed_seconds,
+        report.published,
+        report.publish_rate_per_second,
+        report.target_rate_per_second,
+        report.delivered,
+        report.lost,
+        report.loss_percent,
+        report.out_of_order,
+    );
+    if report.unrecognized > 0 {
+        text.push_str(&format!(
+            "\nIgnored {} messages not sent by this benchmark",
+            report.unrecognized
+        ));
+    }
+    if let Some(latency) = &report.latency_ms {
+        text.push_str(&format!(
+            "\n\nDelivery latency ms: p50 {:.3}, p90 {:.3}, p99 {:.3}, p99.9 {:.3}, max {:.3}",
+            latency.p50, latency.p90, latency.p99, latency.p999, latency.max
+        ));
+    }
+    if report.publish_errors.is_empty() {
+        text.push_str("\n\nPublish errors: none");
+    } else {
+        text.push_str("\n\nPublish errors:");
+        for (code, count) in &report.publish_errors {
+            text.push_str(&format!("\n  {code}: {count}"));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payloads_round_trip() {
+        assert_eq!(
+            parse_payload(&format_payload(3, 42, 1_700_000_000_000_000)),
+            Some((3, 42, 1_700_000_000_000_000))
+        );
+        assert_eq!(parse_payload("hello"), None);
+        assert_eq!(parse_payload("1:2"), None);
+    }
+
+    #[test]
+    fn gaps_count_as_lost_until_filled() {
+        let mut stats = SubscriberStats::default();
+        for sequence in [0, 1, 4, 2] {
+            stats.record(0, sequence, 1000);
+        }
+        stats.record(1, 0, 1000);
+
+        assert_eq!(stats.received, 5);
+        assert_eq!(stats.out_of_order, 1);
+        // Publisher 0 sent 6 (3 and 5 never came); publisher 1 sent 2 (1 never came).
+        assert_eq!(stats.lost(&[6, 2]), 3);
+    }
+
+    #[test]
+    fn report_sums_over_subscribers() {
+        let options = TopicBenchOptions {
+            cache_name: "cache".to_string(),
+            topic: "topic".to_string(),
+            subscribers: 2,
+            publishers: 1,
+            rate: 10,
+            duration: Duration::from_secs(2),
+            output: OutputFormat::Text,
+        };
+        let mut complete = SubscriberStats::default();
+        let mut partial = SubscriberStats::default();
+        for sequence in 0..4 {
+            complete.record(0, sequence, 2000);
+        }
+        partial.record(0, 0, 1000);
+        partial.record(0, 3, 1000);
+
+        let report = summarize(
+            &options,
+            Duration::from_secs(2),
+            &[4],
+            BTreeMap::from([("TimeoutError".to_string(), 1)]),
+            vec![complete, partial],
+        );
+        assert_eq!(
+            format_report(&report),
+            "Topic topic in cache cache: 1 publishers, 2 subscribers, 2.0s
+Published: 4 (2.0/s, target 10/s)
+Delivered: 6 (summed over subscribers)
+Lost: 2 (25.00%), out of order: 0
+
+Delivery latency ms: p50 2.000, p90 2.000, p99 2.000, p99.9 2.000, max 2.000
+
+Publish errors:
+  TimeoutError: 1"
+        );
+    }
+}
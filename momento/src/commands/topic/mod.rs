@@ -1,3 +1,5 @@
+pub mod bench;
+
 use futures::StreamExt;
 use momento::{topics::Subscription, MomentoResult};
 
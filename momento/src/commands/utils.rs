@@ -1,14 +1,71 @@
+use crate::config::HttpSettings;
 use crate::error::CliError;
+use crate::utils::dry_run::{is_dry_run, print_http_request, REDACTED};
+use crate::utils::trace::{record_span, start_span, TraceKind};
 
-use http::Method;
-use log::{info, warn};
+use http::{Method, StatusCode};
+use log::{debug, info, warn};
+use rand::Rng;
 use reqwest;
+use reqwest::header::{HeaderMap, HeaderValue};
 use serde::{de::DeserializeOwned, Deserialize};
 use std::fmt::Debug;
+use std::fs;
+use std::sync::OnceLock;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// One client for every call to the Momento HTTP APIs, so connections (and any proxy tunnel) are reused.
+static HTTP_CLIENT: OnceLock<HttpClient> = OnceLock::new();
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+struct HttpClient {
+    client: reqwest::Client,
+    retry_policy: RetryPolicy,
+}
+
+/// How many times, and how patiently, to send a request that failed transiently.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_settings(settings: &HttpSettings) -> Self {
+        Self {
+            max_attempts: settings.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            ..Default::default()
+        }
+    }
+
+    /// Exponential backoff with full jitter, so concurrent CLIs don't retry in lockstep.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        ceiling.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
 
 pub enum MomentoHttpData {
     Json(serde_json::Value),
-    String(String),
+    Bytes(Vec<u8>),
 }
 
 #[derive(Deserialize)]
@@ -20,71 +77,296 @@ struct MomentoHttpError {
 pub enum MomentoHttpResponse<T> {
     Parsed(T),
     Unparseable(String),
+    /// `--dry-run` printed the request instead of sending it, so there's no response.
+    DryRun,
+}
+
+/// Everything the server sent back, whatever its status.
+pub struct MomentoHttpFullResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
 }
 
+pub fn build_http_client(settings: &HttpSettings) -> Result<reqwest::Client, CliError> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy_url) = &settings.https_proxy {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| {
+                CliError::new(format!("Invalid https_proxy {proxy_url:?}: {e}"))
+                    .with_details(format!("{e:#?}"))
+            })?
+            .no_proxy(
+                settings
+                    .no_proxy
+                    .as_deref()
+                    .and_then(reqwest::NoProxy::from_string),
+            );
+        builder = builder.proxy(proxy);
+    }
+    for path in &settings.ca_bundle_paths {
+        let pem = fs::read(path)
+            .map_err(|e| CliError::new(format!("Could not read CA bundle {path}: {e}")))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            CliError::new(format!("Could not parse CA bundle {path}: {e}"))
+                .with_details(format!("{e:#?}"))
+        })?;
+        if certificates.is_empty() {
+            return Err(CliError::new(format!(
+                "CA bundle {path} has no PEM certificates"
+            )));
+        }
+        debug!("Trusting {} certificate(s) from {path}", certificates.len());
+        builder = builder.tls_certs_merge(certificates);
+    }
+    if let Some(seconds) = settings.request_timeout_seconds {
+        builder = builder.timeout(Duration::from_secs(seconds));
+    }
+    Ok(builder.build()?)
+}
+
+/// Sets up the shared HTTP client; call before the first request.
+/// If a client is already in use, it's kept.
+pub fn init_http_client(settings: &HttpSettings) -> Result<(), CliError> {
+    let client = HttpClient {
+        client: build_http_client(settings)?,
+        retry_policy: RetryPolicy::from_settings(settings),
+    };
+    if HTTP_CLIENT.set(client).is_err() {
+        debug!("HTTP client was already initialized; ignoring new settings");
+    }
+    Ok(())
+}
+
+fn http_client() -> Result<&'static HttpClient, CliError> {
+    if let Some(client) = HTTP_CLIENT.get() {
+        return Ok(client);
+    }
+    let client = HttpClient {
+        client: build_http_client(&HttpSettings::default())?,
+        retry_policy: RetryPolicy::default(),
+    };
+    Ok(HTTP_CLIENT.get_or_init(|| client))
+}
+
+/// The shared HTTP client, with the configured proxy and certificates, for calls that don't go
+/// to the Momento HTTP API.
+pub fn shared_http_client() -> Result<&'static reqwest::Client, CliError> {
+    Ok(&http_client()?.client)
+}
+
+/// Sends a request, retrying transient failures when that's safe; see `is_retry_safe`.
+/// On a dry run, the request is printed instead, and there's no response text.
 async fn call_api(
     method: Method,
     request_url: String,
     auth_token: String,
-    headers: Option<reqwest::header::HeaderMap>,
+    headers: Option<HeaderMap>,
+    data: Option<MomentoHttpData>,
+) -> Result<Option<String>, CliError> {
+    call_momento_http_api_full(method, request_url, auth_token, headers, data)
+        .await?
+        .map(success_text)
+        .transpose()
+}
+
+/// Like `call_api`, but hands back the response as is, rather than treating a non-2xx as an error.
+pub async fn call_momento_http_api_full(
+    method: Method,
+    request_url: String,
+    auth_token: String,
+    headers: Option<HeaderMap>,
+    data: Option<MomentoHttpData>,
+) -> Result<Option<MomentoHttpFullResponse>, CliError> {
+    let http_client = http_client()?;
+    send_with_retries(
+        &http_client.client,
+        http_client.retry_policy,
+        method,
+        request_url,
+        auth_token,
+        headers,
+        data,
+    )
+    .await
+}
+
+async fn send_with_retries(
+    client: &reqwest::Client,
+    retry_policy: RetryPolicy,
+    method: Method,
+    request_url: String,
+    auth_token: String,
+    headers: Option<HeaderMap>,
     data: Option<MomentoHttpData>,
-) -> Result<String, CliError> {
-    let req_client = reqwest::Client::builder().build()?;
-    let request_builder = req_client
-        .request(method.clone(), &request_url)
-        .header("authorization", &auth_token);
-    let request_builder = match data {
-        None => request_builder,
-        Some(MomentoHttpData::Json(data)) => request_builder
-            .body(data.to_string())
-            .header("content-type", "application/json"),
-        Some(MomentoHttpData::String(data)) => request_builder.body(data),
+) -> Result<Option<MomentoHttpFullResponse>, CliError> {
+    let mut headers = headers.unwrap_or_default();
+    if headers.remove("authorization").is_some() {
+        warn!("Removed authorization header; must be specified via --profile or --api-key");
+    }
+    let (body, content_type) = match data {
+        None => (None, None),
+        Some(MomentoHttpData::Json(data)) => (
+            Some(data.to_string().into_bytes()),
+            Some("application/json"),
+        ),
+        Some(MomentoHttpData::Bytes(data)) => (Some(data), None),
     };
-    let request_builder = match headers {
-        None => request_builder,
-        Some(mut headers) => {
-            if headers.remove("authorization").is_some() {
-                warn!("Removed authorization header; must be specified via --profile or --api-key");
-            }
-            request_builder.headers(headers)
+    if is_dry_run() {
+        let mut shown_headers = HeaderMap::new();
+        shown_headers.insert("authorization", HeaderValue::from_static(REDACTED));
+        if let Some(content_type) = content_type {
+            shown_headers.insert("content-type", HeaderValue::from_static(content_type));
         }
+        shown_headers.extend(headers);
+        let shown_body = body.as_ref().map(|body| match std::str::from_utf8(body) {
+            Ok(text) => text.to_string(),
+            Err(_) => format!("<{} bytes of binary data>", body.len()),
+        });
+        print_http_request(&method, &request_url, &shown_headers, shown_body.as_deref());
+        return Ok(None);
+    }
+    let max_attempts = if is_retry_safe(&method, &headers) {
+        retry_policy.max_attempts
+    } else {
+        1
     };
 
-    let response = request_builder.send().await?;
+    let request_bytes = body.as_ref().map_or(0, Vec::len);
+    let started = start_span();
+    let trace_name = format!(
+        "{method} {}",
+        reqwest::Url::parse(&request_url)
+            .map(|url| url.path().to_string())
+            .unwrap_or_else(|_| request_url.clone())
+    );
+
+    let mut attempt = 1;
+    let response = loop {
+        info!("Sending {method} {request_url} (attempt {attempt} of {max_attempts})");
+        let mut request_builder = client
+            .request(method.clone(), &request_url)
+            .header("authorization", &auth_token);
+        if let Some(body) = &body {
+            request_builder = request_builder.body(body.clone());
+        }
+        if let Some(content_type) = content_type {
+            request_builder = request_builder.header("content-type", content_type);
+        }
+        let result = request_builder.headers(headers.clone()).send().await;
+
+        let transient_failure = match &result {
+            Ok(response) if is_transient(response.status()) => Some(response.status().to_string()),
+            Err(e) if e.is_connect() || e.is_timeout() => Some(e.to_string()),
+            _ => None,
+        };
+        match transient_failure {
+            Some(reason) if attempt < max_attempts => {
+                let delay = retry_policy.backoff(attempt);
+                info!("{method} {request_url} failed ({reason}); retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            _ => break result,
+        }
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            let outcome = if e.is_timeout() { "timeout" } else { "error" };
+            record_span(
+                started,
+                TraceKind::Http,
+                trace_name,
+                outcome.to_string(),
+                Some(request_bytes),
+                None,
+            );
+            return Err(e.into());
+        }
+    };
     let status = response.status();
+    let response_headers = response.headers().clone();
 
-    info!(
-        "Headers sent back from {method} {request_url}:\n{:#?}",
-        response.headers()
+    info!("Headers sent back from {method} {request_url}:\n{response_headers:#?}");
+
+    let body = response.bytes().await;
+    record_span(
+        started,
+        TraceKind::Http,
+        trace_name,
+        status.as_u16().to_string(),
+        Some(request_bytes),
+        body.as_ref().ok().map(|body| body.len()),
     );
+    Ok(Some(MomentoHttpFullResponse {
+        status,
+        headers: response_headers,
+        body: body?.to_vec(),
+    }))
+}
 
+/// The body of a 2xx response as text; any other status is an error, with the API's message.
+pub fn success_text(response: MomentoHttpFullResponse) -> Result<String, CliError> {
+    let status = response.status;
+    let response_text = String::from_utf8_lossy(&response.body).to_string();
     if status.is_success() {
-        Ok(response.text().await?)
+        Ok(response_text)
     } else {
-        let error_text = response.text().await?;
-        Err(CliError::new(if error_text.is_empty() {
+        Err(CliError::new(if response_text.is_empty() {
             format!("{status}")
         } else {
-            let error_message = match serde_json::from_str::<MomentoHttpError>(error_text.as_str())
-            {
-                Ok(error) => error
-                    .detail
-                    .unwrap_or(error.message.unwrap_or(error_text.clone())),
-                Err(_) => error_text.clone(),
-            };
+            let error_message =
+                match serde_json::from_str::<MomentoHttpError>(response_text.as_str()) {
+                    Ok(error) => error
+                        .detail
+                        .unwrap_or(error.message.unwrap_or(response_text.clone())),
+                    Err(_) => response_text.clone(),
+                };
             format!("{status}: {error_message}")
         })
-        .with_details(error_text))
+        .with_details(response_text))
+    }
+}
+
+/// Reads and deletes are safe to repeat. Creates and updates are safe only when they carry an
+/// idempotency key, which the API uses to apply a repeated request just once.
+fn is_retry_safe(method: &Method, headers: &HeaderMap) -> bool {
+    match *method {
+        Method::GET | Method::HEAD | Method::DELETE => true,
+        Method::POST | Method::PATCH | Method::PUT => headers.contains_key(IDEMPOTENCY_KEY_HEADER),
+        _ => false,
     }
 }
 
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// A fresh idempotency key, for a create or update that should be retried on transient failures.
+pub fn idempotency_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        IDEMPOTENCY_KEY_HEADER,
+        HeaderValue::from_str(&Uuid::new_v4().to_string())
+            .expect("a UUID should be a valid header value"),
+    );
+    headers
+}
+
 pub async fn call_momento_http_api_raw(
     method: Method,
     request_url: String,
     auth_token: String,
-    headers: Option<reqwest::header::HeaderMap>,
+    headers: Option<HeaderMap>,
     data: Option<MomentoHttpData>,
-) -> Result<String, CliError> {
+) -> Result<Option<String>, CliError> {
     let response_text = call_api(
         method.clone(),
         request_url.clone(),
@@ -93,7 +375,9 @@ pub async fn call_momento_http_api_raw(
         data,
     )
     .await?;
-    info!("Response sent back from {method} {request_url}:\n{response_text}");
+    if let Some(response_text) = &response_text {
+        info!("Response sent back from {method} {request_url}:\n{response_text}");
+    }
     Ok(response_text)
 }
 
@@ -101,17 +385,20 @@ pub async fn call_momento_http_api<T: DeserializeOwned + Debug>(
     method: Method,
     request_url: String,
     auth_token: String,
-    headers: Option<reqwest::header::HeaderMap>,
+    headers: Option<HeaderMap>,
     data: Option<MomentoHttpData>,
 ) -> Result<MomentoHttpResponse<T>, CliError> {
-    let response_text = call_api(
+    let Some(response_text) = call_api(
         method.clone(),
         request_url.clone(),
         auth_token,
         headers,
         data,
     )
-    .await?;
+    .await?
+    else {
+        return Ok(MomentoHttpResponse::DryRun);
+    };
     match serde_json::from_str::<T>(response_text.as_str()) {
         Ok(response) => {
             info!("Response sent back from {method} {request_url}:\n{response:#?}");
@@ -132,3 +419,399 @@ impl From<reqwest::Error> for CliError {
         CliError::new(format!("{e} (reqwest error)")).with_details(format!("{e:#?}"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A private CA, and a `localhost` certificate it issued, as a corporate network might use.
+    const TEST_CA_CERTIFICATE: &str = include_str!("testdata/test_ca.pem");
+    const TEST_SERVER_CERTIFICATE: &str = include_str!("testdata/localhost.pem");
+    const TEST_SERVER_KEY: &str = include_str!("testdata/localhost.key");
+
+    const CANNED_RESPONSE: &str =
+        "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok";
+
+    fn canned_response(status_line: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status_line}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    /// Answers one request per response, in order, and reports the requests it received.
+    async fn serve(responses: Vec<String>) -> (u16, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("should bind a local port");
+        let port = listener.local_addr().expect("should have address").port();
+        let handle = tokio::spawn(async move {
+            let mut requests = vec![];
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.expect("should accept");
+                requests.push(read_request(&mut socket).await);
+                socket
+                    .write_all(response.as_bytes())
+                    .await
+                    .expect("should write response");
+            }
+            requests
+        });
+        (port, handle)
+    }
+
+    /// Answers a single request with `ok`, and reports the request line it received.
+    async fn serve_once() -> (u16, tokio::task::JoinHandle<String>) {
+        let (port, handle) = serve(vec![CANNED_RESPONSE.to_string()]).await;
+        let handle = tokio::spawn(async move {
+            let requests = handle.await.expect("server should finish");
+            request_line(&requests[0])
+        });
+        (port, handle)
+    }
+
+    /// Like `serve_once`, but over TLS with the `localhost` certificate.
+    async fn serve_once_over_tls() -> (u16, tokio::task::JoinHandle<Option<String>>) {
+        use tokio_rustls::rustls::pki_types::pem::PemObject;
+        use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+        let certificates = CertificateDer::pem_slice_iter(TEST_SERVER_CERTIFICATE.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .expect("should parse server certificate");
+        let key = PrivateKeyDer::from_pem_slice(TEST_SERVER_KEY.as_bytes())
+            .expect("should parse server key");
+        let config = tokio_rustls::rustls::ServerConfig::builder_with_provider(
+            std::sync::Arc::new(tokio_rustls::rustls::crypto::aws_lc_rs::default_provider()),
+        )
+        .with_safe_default_protocol_versions()
+        .expect("should support the default protocol versions")
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .expect("should build TLS config");
+        let acceptor = tokio_rustls::TlsAcceptor::from(std::sync::Arc::new(config));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("should bind a local port");
+        let port = listener.local_addr().expect("should have address").port();
+        let handle = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.expect("should accept");
+            // A client that doesn't trust the certificate hangs up during the handshake.
+            let mut socket = acceptor.accept(socket).await.ok()?;
+            let request = read_request(&mut socket).await;
+            socket
+                .write_all(CANNED_RESPONSE.as_bytes())
+                .await
+                .expect("should write response");
+            socket.shutdown().await.ok();
+            Some(request_line(&request))
+        });
+        (port, handle)
+    }
+
+    /// Reads a request's head and body, so the client sees a clean response rather than a reset.
+    async fn read_request(socket: &mut (impl AsyncRead + Unpin)) -> String {
+        let mut request = vec![];
+        let mut buffer = [0; 4096];
+        loop {
+            let read = socket.read(&mut buffer).await.expect("should read request");
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let content_length = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length: "))
+                    .and_then(|length| length.trim().parse::<usize>().ok())
+                    .unwrap_or_default();
+                if read == 0 || body.len() >= content_length {
+                    return text.to_string();
+                }
+            } else if read == 0 {
+                return text.to_string();
+            }
+        }
+    }
+
+    fn request_line(request: &str) -> String {
+        request.lines().next().unwrap_or_default().to_string()
+    }
+
+    fn header_value(request: &str, name: &str) -> Option<String> {
+        request
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{name}: ")))
+            .map(String::from)
+    }
+
+    fn write_temp_file(name: &str, contents: &str) -> (tempdir::TempDir, String) {
+        let dir = tempdir::TempDir::new("momento-cli-http").expect("should create temp dir");
+        let path = dir.path().join(name);
+        fs::File::create(&path)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .expect("should write temp file");
+        let path = path.to_string_lossy().to_string();
+        (dir, path)
+    }
+
+    #[tokio::test]
+    async fn test_http_client_sends_requests_through_proxy() {
+        let (proxy_port, proxy) = serve_once().await;
+        let client = build_http_client(&HttpSettings {
+            https_proxy: Some(format!("http://127.0.0.1:{proxy_port}")),
+            ..Default::default()
+        })
+        .expect("should build client");
+
+        let response = client
+            .get("http://api.cache.example.invalid/capacity_pool")
+            .send()
+            .await
+            .expect("should reach proxy");
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            "GET http://api.cache.example.invalid/capacity_pool HTTP/1.1",
+            proxy.await.expect("proxy should finish")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_client_bypasses_proxy_for_no_proxy_hosts() {
+        let (server_port, server) = serve_once().await;
+        let client = build_http_client(&HttpSettings {
+            // nothing listens here, so only a bypassed request can succeed
+            https_proxy: Some("http://127.0.0.1:9".to_string()),
+            no_proxy: Some("127.0.0.1".to_string()),
+            ..Default::default()
+        })
+        .expect("should build client");
+
+        let response = client
+            .get(format!("http://127.0.0.1:{server_port}/database"))
+            .send()
+            .await
+            .expect("should bypass proxy");
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            "GET /database HTTP/1.1",
+            server.await.expect("server should finish")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_client_trusts_ca_bundle() {
+        let (port, server) = serve_once_over_tls().await;
+        let (_dir, path) = write_temp_file("ca.pem", TEST_CA_CERTIFICATE);
+        let client = build_http_client(&HttpSettings {
+            ca_bundle_paths: vec![path],
+            ..Default::default()
+        })
+        .expect("should build client with extra CA");
+
+        let response = client
+            .get(format!("https://localhost:{port}/functions/cache/fn"))
+            .send()
+            .await
+            .expect("should trust the server's certificate");
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            Some("GET /functions/cache/fn HTTP/1.1".to_string()),
+            server.await.expect("server should finish")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_client_distrusts_unknown_ca() {
+        let (port, server) = serve_once_over_tls().await;
+        let client =
+            build_http_client(&HttpSettings::default()).expect("should build default client");
+
+        client
+            .get(format!("https://localhost:{port}/functions/cache/fn"))
+            .send()
+            .await
+            .expect_err("should not trust the server's certificate");
+
+        assert_eq!(None, server.await.expect("server should finish"));
+    }
+
+    #[test]
+    fn test_http_client_rejects_ca_bundle_without_certificates() {
+        let (_dir, path) = write_temp_file("ca.pem", "not a certificate");
+
+        let err = build_http_client(&HttpSettings {
+            ca_bundle_paths: vec![path.clone()],
+            ..Default::default()
+        })
+        .expect_err("should reject CA bundle");
+
+        assert_eq!(format!("CA bundle {path} has no PEM certificates"), err.msg);
+    }
+
+    #[test]
+    fn test_http_client_rejects_missing_ca_bundle() {
+        let err = build_http_client(&HttpSettings {
+            ca_bundle_paths: vec!["/nonexistent/ca.pem".to_string()],
+            ..Default::default()
+        })
+        .expect_err("should reject CA bundle");
+
+        assert!(
+            err.msg
+                .starts_with("Could not read CA bundle /nonexistent/ca.pem"),
+            "unexpected error: {}",
+            err.msg
+        );
+    }
+
+    fn quick_retry_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    async fn send_to_local_server(
+        port: u16,
+        method: Method,
+        headers: Option<HeaderMap>,
+        data: Option<MomentoHttpData>,
+    ) -> Result<String, CliError> {
+        let client = build_http_client(&HttpSettings::default()).expect("should build client");
+        send_with_retries(
+            &client,
+            quick_retry_policy(),
+            method,
+            format!("http://127.0.0.1:{port}/capacity_pool/my-pool"),
+            "token".to_string(),
+            headers,
+            data,
+        )
+        .await?
+        .map(success_text)
+        .expect("only a dry run has no response")
+    }
+
+    #[test]
+    fn test_retry_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        for _ in 0..100 {
+            assert!(policy.backoff(1) <= Duration::from_millis(100));
+            assert!(policy.backoff(3) <= Duration::from_millis(400));
+            assert!(policy.backoff(9) <= Duration::from_millis(500));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_get_after_transient_failure() {
+        let (port, server) = serve(vec![
+            canned_response("503 Service Unavailable", ""),
+            canned_response("200 OK", "{\"name\": \"my-pool\"}"),
+        ])
+        .await;
+
+        let response = send_to_local_server(port, Method::GET, None, None)
+            .await
+            .expect("should succeed on retry");
+
+        assert_eq!("{\"name\": \"my-pool\"}", response);
+        assert_eq!(2, server.await.expect("server should finish").len());
+    }
+
+    #[tokio::test]
+    async fn test_retries_delete_until_max_attempts() {
+        let (port, server) = serve(vec![
+            canned_response("503 Service Unavailable", ""),
+            canned_response("502 Bad Gateway", ""),
+            canned_response("429 Too Many Requests", "{\"message\": \"slow down\"}"),
+        ])
+        .await;
+
+        let err = send_to_local_server(port, Method::DELETE, None, None)
+            .await
+            .expect_err("should fail after max attempts");
+
+        assert_eq!("429 Too Many Requests: slow down", err.msg);
+        assert_eq!(3, server.await.expect("server should finish").len());
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_post_without_idempotency_key() {
+        let (port, server) = serve(vec![canned_response("503 Service Unavailable", "")]).await;
+
+        let err = send_to_local_server(
+            port,
+            Method::POST,
+            None,
+            Some(MomentoHttpData::Json(
+                serde_json::json!({"provisioning": {}}),
+            )),
+        )
+        .await
+        .expect_err("should not retry");
+
+        assert_eq!("503 Service Unavailable", err.msg);
+        assert_eq!(1, server.await.expect("server should finish").len());
+    }
+
+    #[tokio::test]
+    async fn test_retries_patch_with_the_same_idempotency_key() {
+        let (port, server) = serve(vec![
+            canned_response("504 Gateway Timeout", ""),
+            canned_response("200 OK", "{}"),
+        ])
+        .await;
+
+        send_to_local_server(
+            port,
+            Method::PATCH,
+            Some(idempotency_headers()),
+            Some(MomentoHttpData::Json(
+                serde_json::json!({"provisioning": {}}),
+            )),
+        )
+        .await
+        .expect("should succeed on retry");
+
+        let requests = server.await.expect("server should finish");
+        assert_eq!(2, requests.len());
+        let first_key = header_value(&requests[0], IDEMPOTENCY_KEY_HEADER);
+        assert!(first_key.is_some(), "should send an idempotency key");
+        assert_eq!(
+            first_key,
+            header_value(&requests[1], IDEMPOTENCY_KEY_HEADER)
+        );
+        assert!(
+            requests[1].ends_with("{\"provisioning\":{}}"),
+            "should resend the body, got:\n{}",
+            requests[1]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_client_errors() {
+        let (port, server) = serve(vec![canned_response(
+            "400 Bad Request",
+            "{\"detail\": \"zones must be ids\"}",
+        )])
+        .await;
+
+        let err = send_to_local_server(port, Method::GET, None, None)
+            .await
+            .expect_err("should not retry");
+
+        assert_eq!("400 Bad Request: zones must be ids", err.msg);
+        assert_eq!(1, server.await.expect("server should finish").len());
+    }
+}
@@ -9,16 +9,26 @@ use momento::MomentoError;
 use momento_cli_opts::PreviewCommand;
 use utils::{
     client::{get_cache_client, get_function_client, get_topic_client},
+    confirm::Safeguards,
     console::output_info,
+    dry_run::{enable_dry_run, skip_sdk_request},
+    trace::{enable_trace, finish_trace},
     user::get_creds_and_config,
 };
 
 use crate::{
-    commands::capacity_pool::utils::{determine_provisioning, determine_provisioning_update},
+    commands::capacity_pool::utils::{
+        determine_provisioning, determine_provisioning_update, read_provisioning_spec,
+    },
+    commands::capacity_pool::wait::PoolTarget,
+    commands::capacity_pool::zones::resolve_zones,
+    commands::database::database_cli::ConnectionOptions,
+    commands::functions::deploy::DeployOptions,
     commands::functions::utils::{
         determine_current_function_version, determine_metrics_config_change, determine_wasm_source,
         InvocationOptions,
     },
+    commands::utils::init_http_client,
     utils::console::console_info,
 };
 
@@ -43,7 +53,11 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                     cache_name_flag,
                     cache_name,
                     cache_name_flag_for_backward_compatibility,
+                    from_file,
                 } => {
+                    if let Some(path) = from_file {
+                        return commands::cache::bulk::create_caches_from_file(client, path).await;
+                    }
                     let cache_name = cache_name
                         .or(cache_name_flag)
                         .or(cache_name_flag_for_backward_compatibility)
@@ -55,25 +69,61 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                     cache_name,
                     cache_name_flag,
                     cache_name_flag_for_backward_compatibility,
+                    match_pattern,
+                    yes,
+                    force,
                 } => {
+                    let safeguards = Safeguards {
+                        yes,
+                        force,
+                        protected: config.protected.caches,
+                    };
+                    if let Some(pattern) = match_pattern {
+                        return commands::cache::bulk::delete_matching_caches(
+                            client, pattern, safeguards,
+                        )
+                        .await;
+                    }
                     let cache_name = cache_name
                         .or(cache_name_flag)
                         .or(cache_name_flag_for_backward_compatibility)
                         .expect("The argument group guarantees 1 or the other");
-                    commands::cache::cache_cli::delete_cache(client, cache_name.clone()).await?;
+                    commands::cache::cache_cli::delete_cache(
+                        client,
+                        cache_name.clone(),
+                        safeguards,
+                    )
+                    .await?;
                     debug!("deleted cache {}", cache_name)
                 }
-                momento_cli_opts::CacheCommand::List {} => {
-                    commands::cache::cache_cli::list_caches(client).await?
+                momento_cli_opts::CacheCommand::List { long, output } => {
+                    commands::cache::cache_cli::list_caches(client, long, output).await?
+                }
+                momento_cli_opts::CacheCommand::Describe {
+                    cache_name,
+                    cache_name_flag,
+                    output,
+                } => {
+                    let cache_name = cache_name
+                        .or(cache_name_flag)
+                        .expect("The argument group guarantees 1 or the other");
+                    commands::cache::cache_cli::describe_cache(client, cache_name, output).await?
                 }
                 momento_cli_opts::CacheCommand::Flush {
                     cache_name,
                     cache_name_flag,
+                    yes,
+                    force,
                 } => {
                     let cache_name = cache_name
                         .or(cache_name_flag)
                         .expect("The argument group guarantees 1 or the other");
-                    commands::cache::cache_cli::flush_cache(client, cache_name).await?
+                    let safeguards = Safeguards {
+                        yes,
+                        force,
+                        protected: config.protected.caches,
+                    };
+                    commands::cache::cache_cli::flush_cache(client, cache_name, safeguards).await?
                 }
                 momento_cli_opts::CacheCommand::Set {
                     cache_name,
@@ -138,6 +188,44 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                     )
                     .await?;
                 }
+                momento_cli_opts::CacheCommand::Ping {
+                    cache_name,
+                    max_latency,
+                    watch,
+                } => {
+                    commands::cache::ping::ping(
+                        client,
+                        cache_name.unwrap_or(config.cache),
+                        max_latency,
+                        watch,
+                    )
+                    .await?;
+                }
+                momento_cli_opts::CacheCommand::Bench {
+                    cache_name,
+                    workers,
+                    duration,
+                    ratio,
+                    value_size,
+                    keyspace,
+                    rate,
+                    output,
+                } => {
+                    commands::cache::bench::bench(
+                        client,
+                        commands::cache::bench::BenchOptions {
+                            cache_name: cache_name.unwrap_or(config.cache),
+                            workers,
+                            duration,
+                            ratio,
+                            value_size,
+                            keyspace,
+                            rate,
+                            output,
+                        },
+                    )
+                    .await?;
+                }
             }
         }
         momento_cli_opts::Subcommand::Topic {
@@ -156,6 +244,16 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                     value,
                 } => {
                     let cache_name = cache_name.unwrap_or(config.cache);
+                    if skip_sdk_request(
+                        "TopicPublish",
+                        &[
+                            ("cache_name", cache_name.clone()),
+                            ("topic", topic.clone()),
+                            ("value", value.clone()),
+                        ],
+                    ) {
+                        return Ok(());
+                    }
                     client
                         .publish(cache_name, topic, value)
                         .await
@@ -163,6 +261,12 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                 }
                 momento_cli_opts::TopicCommand::Subscribe { cache_name, topic } => {
                     let cache_name = cache_name.unwrap_or(config.cache);
+                    if skip_sdk_request(
+                        "TopicSubscribe",
+                        &[("cache_name", cache_name.clone()), ("topic", topic.clone())],
+                    ) {
+                        return Ok(());
+                    }
                     let subscription = client.subscribe(cache_name, topic).await.map_err(|e| {
                         CliError::new(format!(
                             "the subscription ended without receiving any values: {e:?}"
@@ -177,6 +281,29 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         }
                     }
                 }
+                momento_cli_opts::TopicCommand::Bench {
+                    cache_name,
+                    topic,
+                    subscribers,
+                    publishers,
+                    rate,
+                    duration,
+                    output,
+                } => {
+                    commands::topic::bench::bench(
+                        client,
+                        commands::topic::bench::TopicBenchOptions {
+                            cache_name: cache_name.unwrap_or(config.cache),
+                            topic,
+                            subscribers,
+                            publishers,
+                            rate,
+                            duration,
+                            output,
+                        },
+                    )
+                    .await?;
+                }
             }
         }
         momento_cli_opts::Subcommand::Configure {
@@ -230,10 +357,11 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
             } => {
                 let (creds, config) = get_creds_and_config(&args.profile).await?;
                 let credential_provider = creds.override_and_authenticate(api_key, endpoint)?;
+                init_http_client(&config.http)?;
 
                 let api_endpoint = credential_provider.cache_http_endpoint().to_string();
                 let auth_token = credential_provider.auth_token().to_string();
-                let client = get_function_client(credential_provider).await?;
+                let client = get_function_client(credential_provider.clone()).await?;
 
                 match operation {
                     momento_cli_opts::FunctionCommand::PutFunction {
@@ -244,11 +372,22 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         version_uploaded_wasm,
                         description,
                         environment_variables,
+                        env_file,
+                        env_from_cache,
                         metrics_iam_role,
                         disable_metrics,
                         remove_metrics_config,
                     } => {
                         let cache_name = cache_name.unwrap_or(config.cache);
+                        let environment_variables =
+                            commands::functions::function_cli::collect_environment(
+                                credential_provider,
+                                &cache_name,
+                                env_file,
+                                env_from_cache,
+                                environment_variables,
+                            )
+                            .await?;
                         let wasm_source = determine_wasm_source(
                             wasm_file,
                             id_uploaded_wasm,
@@ -260,7 +399,7 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                             remove_metrics_config,
                         );
                         commands::functions::function_cli::put_function(
-                            client,
+                            &client,
                             cache_name,
                             name,
                             wasm_source,
@@ -270,6 +409,39 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         )
                         .await?
                     }
+                    momento_cli_opts::FunctionCommand::Deploy {
+                        cache_name,
+                        name,
+                        manifest_path,
+                        description,
+                        environment_variables,
+                        env_file,
+                        env_from_cache,
+                        watch,
+                    } => {
+                        let cache_name = cache_name.unwrap_or(config.cache);
+                        let environment_variables =
+                            commands::functions::function_cli::collect_environment(
+                                credential_provider,
+                                &cache_name,
+                                env_file,
+                                env_from_cache,
+                                environment_variables,
+                            )
+                            .await?;
+                        commands::functions::deploy::deploy(
+                            client,
+                            DeployOptions {
+                                cache_name,
+                                name,
+                                manifest_path,
+                                description,
+                                environment_variables,
+                                watch,
+                            },
+                        )
+                        .await?
+                    }
                     momento_cli_opts::FunctionCommand::PutFunctionConfig {
                         cache_name,
                         function_name,
@@ -316,6 +488,10 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         name,
                         method,
                         data,
+                        data_file,
+                        data_stdin,
+                        include,
+                        output_file,
                         headers,
                         path,
                     } => {
@@ -328,8 +504,12 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                             method,
                             InvocationOptions {
                                 data,
+                                data_file,
+                                data_stdin,
                                 headers,
                                 path,
+                                include,
+                                output_file,
                             },
                         )
                         .await?
@@ -346,18 +526,115 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         )
                         .await?
                     }
+                    momento_cli_opts::FunctionCommand::Rollback {
+                        cache_name,
+                        name,
+                        to,
+                        previous: _,
+                    } => {
+                        commands::functions::function_cli::rollback_function(
+                            client,
+                            cache_name.unwrap_or(config.cache),
+                            name,
+                            to,
+                        )
+                        .await?
+                    }
+                    momento_cli_opts::FunctionCommand::Diff {
+                        cache_name,
+                        name,
+                        from,
+                        to,
+                    } => {
+                        commands::functions::function_cli::diff_function_versions(
+                            client,
+                            cache_name.unwrap_or(config.cache),
+                            name,
+                            from,
+                            to,
+                        )
+                        .await?
+                    }
                     momento_cli_opts::FunctionCommand::ListWasms {} => {
                         commands::functions::function_cli::list_wasms(client).await?
                     }
+                    momento_cli_opts::FunctionCommand::DescribeWasm { cache_name, wasm } => {
+                        commands::functions::function_cli::describe_wasm(
+                            client,
+                            cache_name.unwrap_or(config.cache),
+                            wasm,
+                        )
+                        .await?
+                    }
+                    momento_cli_opts::FunctionCommand::PruneWasms { keep } => {
+                        commands::functions::function_cli::prune_wasms(
+                            client,
+                            credential_provider,
+                            keep,
+                        )
+                        .await?
+                    }
                 }
             }
+            // Estimates are worked out locally, so they don't need a profile.
+            PreviewCommand::Pool {
+                operation:
+                    momento_cli_opts::CapacityPoolCommand::Estimate {
+                        instance_type,
+                        shard_count,
+                        replicas_per_shard,
+                        capacity_gib,
+                        zones,
+                        spec,
+                        price_table,
+                    },
+                ..
+            } => {
+                let provisioning = match (spec, replicas_per_shard) {
+                    (Some(spec), _) => read_provisioning_spec(&spec)?,
+                    (None, Some(replicas_per_shard)) => determine_provisioning(
+                        instance_type,
+                        shard_count,
+                        replicas_per_shard,
+                        capacity_gib,
+                        zones.iter().map(ToString::to_string).collect(),
+                    )?,
+                    (None, None) => {
+                        return Err(CliError::new("Missing --replicas-per-shard or --spec"))
+                    }
+                };
+                commands::capacity_pool::estimate::estimate_pool(provisioning, price_table)?
+            }
+            // Recommendations come from the linter's results, and AWS for zone names, so they
+            // don't need a profile either.
+            PreviewCommand::Pool {
+                operation:
+                    momento_cli_opts::CapacityPoolCommand::Recommend {
+                        from,
+                        mode,
+                        translate_zone_names,
+                        aws_profile,
+                        output_dir,
+                    },
+                ..
+            } => {
+                commands::capacity_pool::recommend::recommend_pools(
+                    from,
+                    mode,
+                    translate_zone_names,
+                    aws_profile,
+                    output_dir,
+                )
+                .await?
+            }
             PreviewCommand::Pool {
                 api_key,
                 endpoint,
                 operation,
             } => {
-                let (creds, _) = get_creds_and_config(&args.profile).await?;
+                let (creds, config) = get_creds_and_config(&args.profile).await?;
                 let credential_provider = creds.override_and_authenticate(api_key, endpoint)?;
+                init_http_client(&config.http)?;
 
                 let api_endpoint = credential_provider.cache_http_endpoint().to_string();
                 let auth_token = credential_provider.auth_token().to_string();
@@ -370,19 +647,31 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         replicas_per_shard,
                         capacity_gib,
                         zones,
+                        translate_zone_names,
+                        aws_profile,
+                        spec,
+                        wait,
                     } => {
-                        let provisioning = determine_provisioning(
-                            instance_type,
-                            shard_count,
-                            replicas_per_shard,
-                            capacity_gib,
-                            zones,
-                        )?;
+                        let zones = resolve_zones(zones, translate_zone_names, aws_profile).await?;
+                        let provisioning = match (spec, replicas_per_shard) {
+                            (Some(spec), _) => read_provisioning_spec(&spec)?,
+                            (None, Some(replicas_per_shard)) => determine_provisioning(
+                                instance_type,
+                                shard_count,
+                                replicas_per_shard,
+                                capacity_gib,
+                                zones,
+                            )?,
+                            (None, None) => {
+                                return Err(CliError::new("Missing --replicas-per-shard or --spec"))
+                            }
+                        };
                         commands::capacity_pool::pool_cli::create_pool(
                             api_endpoint,
                             auth_token,
                             name,
                             provisioning,
+                            wait,
                         )
                         .await?
                     }
@@ -394,11 +683,12 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         )
                         .await?
                     }
-                    momento_cli_opts::CapacityPoolCommand::Describe { name } => {
+                    momento_cli_opts::CapacityPoolCommand::Describe { name, watch } => {
                         commands::capacity_pool::pool_cli::describe_pool(
                             api_endpoint,
                             auth_token,
                             name,
+                            watch,
                         )
                         .await?
                     }
@@ -410,35 +700,82 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         replicas_per_shard,
                         capacity_gib,
                         zones,
+                        translate_zone_names,
+                        aws_profile,
+                        spec,
+                        wait,
                     } => {
-                        let provisioning_update = determine_provisioning_update(
-                            mode,
-                            instance_type,
-                            shard_count,
-                            replicas_per_shard,
-                            capacity_gib,
-                            zones,
-                        )?;
-                        commands::capacity_pool::pool_cli::update_pool(
-                            api_endpoint,
-                            auth_token,
-                            name,
-                            provisioning_update,
-                        )
-                        .await?
+                        if let Some(spec) = spec {
+                            commands::capacity_pool::pool_cli::update_pool_from_spec(
+                                api_endpoint,
+                                auth_token,
+                                name,
+                                read_provisioning_spec(&spec)?,
+                                wait,
+                            )
+                            .await?
+                        } else {
+                            let zones =
+                                resolve_zones(zones, translate_zone_names, aws_profile).await?;
+                            let provisioning_update = determine_provisioning_update(
+                                mode,
+                                instance_type,
+                                shard_count,
+                                replicas_per_shard,
+                                capacity_gib,
+                                zones,
+                            )?;
+                            commands::capacity_pool::pool_cli::update_pool(
+                                api_endpoint,
+                                auth_token,
+                                name,
+                                provisioning_update,
+                                wait,
+                            )
+                            .await?
+                        }
                     }
-                    momento_cli_opts::CapacityPoolCommand::Delete { name } => {
+                    momento_cli_opts::CapacityPoolCommand::Delete {
+                        name,
+                        yes,
+                        force,
+                        wait,
+                    } => {
                         commands::capacity_pool::pool_cli::delete_pool(
                             api_endpoint,
                             auth_token,
                             name,
+                            Safeguards {
+                                yes,
+                                force,
+                                protected: config.protected.pools,
+                            },
+                            wait,
                         )
                         .await?
                     }
+                    momento_cli_opts::CapacityPoolCommand::Estimate { .. }
+                    | momento_cli_opts::CapacityPoolCommand::Recommend { .. } => {
+                        unreachable!("handled before loading credentials")
+                    }
                     momento_cli_opts::CapacityPoolCommand::List {} => {
                         commands::capacity_pool::pool_cli::list_pools(api_endpoint, auth_token)
                             .await?
                     }
+                    momento_cli_opts::CapacityPoolCommand::Wait {
+                        name,
+                        status,
+                        timeout,
+                    } => {
+                        commands::capacity_pool::wait::wait_for_pool(
+                            api_endpoint,
+                            auth_token,
+                            name,
+                            PoolTarget::from(status),
+                            timeout,
+                        )
+                        .await?
+                    }
                 }
             }
             PreviewCommand::Database {
@@ -446,8 +783,9 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                 endpoint,
                 operation,
             } => {
-                let (creds, _) = get_creds_and_config(&args.profile).await?;
+                let (creds, config) = get_creds_and_config(&args.profile).await?;
                 let credential_provider = creds.override_and_authenticate(api_key, endpoint)?;
+                init_http_client(&config.http)?;
 
                 let api_endpoint = credential_provider.cache_http_endpoint().to_string();
                 let valkey_hostname = credential_provider.valkey_hostname().to_string();
@@ -473,11 +811,30 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         )
                         .await?
                     }
-                    momento_cli_opts::DatabaseCommand::Delete { name } => {
+                    momento_cli_opts::DatabaseCommand::Update {
+                        name,
+                        pool_name,
+                        wait,
+                    } => {
+                        commands::database::database_cli::update_database(
+                            api_endpoint,
+                            auth_token,
+                            name,
+                            pool_name,
+                            wait,
+                        )
+                        .await?
+                    }
+                    momento_cli_opts::DatabaseCommand::Delete { name, yes, force } => {
                         commands::database::database_cli::delete_database(
                             api_endpoint,
                             auth_token,
                             name,
+                            Safeguards {
+                                yes,
+                                force,
+                                protected: config.protected.databases,
+                            },
                         )
                         .await?
                     }
@@ -489,6 +846,75 @@ async fn run_momento_command(args: momento_cli_opts::Momento) -> Result<(), CliE
                         )
                         .await?
                     }
+                    momento_cli_opts::DatabaseCommand::Connect { name, exec, port } => {
+                        commands::database::database_cli::connect_database(
+                            ConnectionOptions {
+                                valkey_hostname,
+                                port,
+                                database_name: name,
+                                auth_token,
+                                ca_bundle_paths: config.http.ca_bundle_paths,
+                            },
+                            exec,
+                        )
+                        .await?
+                    }
+                    momento_cli_opts::DatabaseCommand::Credentials {
+                        name,
+                        format,
+                        token,
+                        token_ttl,
+                        port,
+                    } => {
+                        commands::database::database_cli::database_credentials(
+                            api_endpoint,
+                            valkey_hostname,
+                            credential_provider,
+                            name,
+                            port,
+                            format,
+                            token,
+                            token_ttl,
+                        )
+                        .await?
+                    }
+                    momento_cli_opts::DatabaseCommand::Ping { name, port } => {
+                        commands::database::database_cli::ping_database(ConnectionOptions {
+                            valkey_hostname,
+                            port,
+                            database_name: name,
+                            auth_token,
+                            ca_bundle_paths: config.http.ca_bundle_paths,
+                        })
+                        .await?
+                    }
+                    momento_cli_opts::DatabaseCommand::Migrate {
+                        name,
+                        source,
+                        concurrency,
+                        checkpoint,
+                        restart,
+                        recreate,
+                        no_verify,
+                        port,
+                    } => {
+                        commands::database::database_cli::migrate_database(
+                            ConnectionOptions {
+                                valkey_hostname,
+                                port,
+                                database_name: name,
+                                auth_token,
+                                ca_bundle_paths: config.http.ca_bundle_paths,
+                            },
+                            source,
+                            concurrency,
+                            checkpoint,
+                            restart,
+                            recreate,
+                            !no_verify,
+                        )
+                        .await?
+                    }
                 }
             }
         },
@@ -536,7 +962,16 @@ async fn main() {
     )
     .init();
 
-    if let Err(e) = run_momento_command(args).await {
+    if args.dry_run {
+        enable_dry_run();
+    }
+    if args.trace || args.trace_file.is_some() {
+        enable_trace(args.trace_file.clone());
+    }
+
+    let result = run_momento_command(args).await;
+    finish_trace();
+    if let Err(e) = result {
         warn!("{e:#?}"); // only in verbose mode (error!() would always output)
         console_info!("{e}");
         exit(1)
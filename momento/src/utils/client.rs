@@ -1,11 +1,14 @@
 use std::{future::Future, time::Duration};
 
 use momento::{
-    cache::configurations, topics, CacheClient, CredentialProvider, FunctionClient, MomentoError,
-    TopicClient,
+    cache::configurations, topics, AuthClient, CacheClient, CredentialProvider, FunctionClient,
+    MomentoError, TopicClient,
 };
 
-use crate::error::CliError;
+use crate::{
+    error::CliError,
+    utils::trace::{record_span, start_span, TraceKind},
+};
 
 pub async fn get_cache_client(
     credential_provider: CredentialProvider,
@@ -27,6 +30,15 @@ pub async fn get_function_client(
         .map_err(Into::<CliError>::into)
 }
 
+pub async fn get_auth_client(
+    credential_provider: CredentialProvider,
+) -> Result<AuthClient, CliError> {
+    AuthClient::builder()
+        .credential_provider(credential_provider)
+        .build()
+        .map_err(Into::<CliError>::into)
+}
+
 pub async fn get_topic_client(
     credential_provider: CredentialProvider,
 ) -> Result<TopicClient, CliError> {
@@ -46,6 +58,18 @@ where
 {
     log::debug!("{}", debug_note);
 
+    let started = start_span();
     let result = momento_interaction.await;
+    record_span(
+        started,
+        TraceKind::Sdk,
+        debug_note.trim_end_matches("...").to_string(),
+        match &result {
+            Ok(_) => "ok".to_string(),
+            Err(e) => format!("{:?}", e.error_code),
+        },
+        None,
+        None,
+    );
     result.map_err(Into::<CliError>::into)
 }
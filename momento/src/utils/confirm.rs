@@ -0,0 +1,153 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use crate::{error::CliError, utils::dry_run::is_dry_run};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Cache,
+    CapacityPool,
+    Database,
+}
+
+impl ResourceKind {
+    fn noun(&self) -> &'static str {
+        match self {
+            ResourceKind::Cache => "cache",
+            ResourceKind::CapacityPool => "capacity pool",
+            ResourceKind::Database => "database",
+        }
+    }
+
+    fn protected_setting(&self) -> &'static str {
+        match self {
+            ResourceKind::Cache => "protected_caches",
+            ResourceKind::CapacityPool => "protected_pools",
+            ResourceKind::Database => "protected_databases",
+        }
+    }
+}
+
+/// What stands between a destructive command and the resource it destroys.
+#[derive(Debug, Clone, Default)]
+pub struct Safeguards {
+    /// `--yes`: don't ask for confirmation.
+    pub yes: bool,
+    /// `--force`: go ahead even if the resource is protected.
+    pub force: bool,
+    /// The profile's protected names for this kind of resource.
+    pub protected: Vec<String>,
+}
+
+impl Safeguards {
+    /// Refuses protected resources without `--force`. Then, unless `--yes` or stdin isn't a
+    /// terminal, asks the user to type the resource's name.
+    pub fn check(&self, kind: ResourceKind, action: &str, name: &str) -> Result<(), CliError> {
+        self.check_protection(kind, action, name)?;
+        if self.yes || is_dry_run() || !io::stdin().is_terminal() {
+            return Ok(());
+        }
+        eprint!(
+            "This will {action} {} {name}. Type its name to confirm: ",
+            kind.noun()
+        );
+        io::stderr().flush()?;
+        confirm_name(kind, name, io::stdin().lock())
+    }
+
+    /// Like `check`, for many resources at once: the user types the pattern that selected them.
+    /// Protected resources should already have been left out.
+    pub fn confirm_bulk(
+        &self,
+        kind: ResourceKind,
+        action: &str,
+        pattern: &str,
+        count: usize,
+    ) -> Result<(), CliError> {
+        if self.yes || is_dry_run() || !io::stdin().is_terminal() {
+            return Ok(());
+        }
+        eprint!(
+            "This will {action} {count} {}(s) matching {pattern}. Type the pattern to confirm: ",
+            kind.noun()
+        );
+        io::stderr().flush()?;
+        confirm_name(kind, pattern, io::stdin().lock())
+    }
+
+    fn check_protection(
+        &self,
+        kind: ResourceKind,
+        action: &str,
+        name: &str,
+    ) -> Result<(), CliError> {
+        if self.force || !self.protected.iter().any(|protected| protected == name) {
+            return Ok(());
+        }
+        Err(CliError::new(format!(
+            "Refusing to {action} {} {name}: it's in this profile's {}. Pass --force to {action} it anyway.",
+            kind.noun(),
+            kind.protected_setting()
+        )))
+    }
+}
+
+fn confirm_name(kind: ResourceKind, name: &str, mut input: impl BufRead) -> Result<(), CliError> {
+    let mut typed = String::new();
+    input.read_line(&mut typed)?;
+    if typed.trim() == name {
+        Ok(())
+    } else {
+        Err(CliError::new(format!(
+            "The name didn't match, so {} {name} was left alone",
+            kind.noun()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protecting(names: &[&str]) -> Safeguards {
+        Safeguards {
+            protected: names.iter().map(|name| name.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn protected_names_need_force() {
+        let safeguards = protecting(&["prod"]);
+        assert!(safeguards
+            .check_protection(ResourceKind::Cache, "delete", "staging")
+            .is_ok());
+
+        let refusal = safeguards
+            .check_protection(ResourceKind::Cache, "flush", "prod")
+            .expect_err("prod is protected");
+        assert_eq!(
+            refusal.msg,
+            "Refusing to flush cache prod: it's in this profile's protected_caches. Pass --force to flush it anyway."
+        );
+
+        let forced = Safeguards {
+            force: true,
+            ..safeguards
+        };
+        assert!(forced
+            .check_protection(ResourceKind::Cache, "delete", "prod")
+            .is_ok());
+    }
+
+    #[test]
+    fn confirmation_requires_the_exact_name() {
+        assert!(confirm_name(ResourceKind::Database, "orders", "orders\n".as_bytes()).is_ok());
+        let mismatch = confirm_name(ResourceKind::Database, "orders", "y\n".as_bytes())
+            .expect_err("y isn't the name");
+        assert_eq!(
+            mismatch.msg,
+            "The name didn't match, so database orders was left alone"
+        );
+        assert!(confirm_name(ResourceKind::CapacityPool, "pool", "".as_bytes()).is_err());
+    }
+}
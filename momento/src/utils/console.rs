@@ -1,3 +1,5 @@
+use std::io::{self, IsTerminal, Write};
+
 /// Write an `info` level `&str` to the console.
 ///
 /// Since we reserve detailed log messages when the cli is `verbose`,
@@ -64,3 +66,14 @@ macro_rules! console_data {
 }
 
 pub(crate) use console_data;
+
+/// Clears the screen and moves the cursor home, so the next output redraws it.
+///
+/// Only a terminal is cleared; when stdout is piped or redirected, nothing is written.
+pub fn clear_screen() {
+    let mut stdout = io::stdout();
+    if stdout.is_terminal() {
+        print!("\x1B[2J\x1B[H");
+        stdout.flush().ok();
+    }
+}
@@ -0,0 +1,179 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use reqwest::header::HeaderMap;
+
+use crate::utils::console::console_data;
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+pub const REDACTED: &str = "<redacted>";
+
+/// Substrings of header, field, and environment variable names whose values are credentials.
+const SECRET_NAME_PARTS: [&str; 9] = [
+    "authorization",
+    "cookie",
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "credential",
+    "private",
+    "api_key",
+];
+
+/// With `--dry-run`, commands print the requests they'd send to Momento instead of sending them.
+pub fn enable_dry_run() {
+    DRY_RUN.store(true, Ordering::Relaxed);
+}
+
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+pub fn is_secret_name(name: &str) -> bool {
+    let name = name.to_lowercase().replace('-', "_");
+    (name.ends_with("_key") && !name.ends_with("idempotency_key"))
+        || SECRET_NAME_PARTS.iter().any(|part| name.contains(part))
+}
+
+/// Renders an HTTP request like `curl -v` would, without credentials.
+pub fn format_http_request(
+    method: &http::Method,
+    request_url: &str,
+    headers: &HeaderMap,
+    body: Option<&str>,
+) -> String {
+    let mut request = format!("{method} {request_url}");
+    for (name, value) in headers {
+        let value = if is_secret_name(name.as_str()) {
+            REDACTED.to_string()
+        } else {
+            String::from_utf8_lossy(value.as_bytes()).to_string()
+        };
+        request.push_str(&format!("\n{name}: {value}"));
+    }
+    if let Some(body) = body.filter(|body| !body.is_empty()) {
+        let body = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok())
+            .unwrap_or_else(|| body.to_string());
+        request.push_str(&format!("\n\n{body}"));
+    }
+    request
+}
+
+/// Renders an SDK request as its operation name followed by its fields, without credentials.
+pub fn format_sdk_request(operation: &str, fields: &[(&str, String)]) -> String {
+    let mut request = operation.to_string();
+    for (name, value) in fields {
+        let value = if is_secret_name(name) {
+            REDACTED
+        } else {
+            value
+        };
+        request.push_str(&format!("\n{name}: {value}"));
+    }
+    request
+}
+
+pub fn print_http_request(
+    method: &http::Method,
+    request_url: &str,
+    headers: &HeaderMap,
+    body: Option<&str>,
+) {
+    console_data!(
+        "Dry run; not sending:\n\n{}",
+        format_http_request(method, request_url, headers, body)
+    );
+}
+
+/// In a dry run, prints the SDK request and returns `true`; the caller should then not send it.
+pub fn skip_sdk_request(operation: &str, fields: &[(&str, String)]) -> bool {
+    if is_dry_run() {
+        console_data!(
+            "Dry run; not sending:\n\n{}",
+            format_sdk_request(operation, fields)
+        );
+    }
+    is_dry_run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_is_secret_name() {
+        for name in [
+            "authorization",
+            "Proxy-Authorization",
+            "x-api-key",
+            "DB_PASSWORD",
+            "GITHUB_TOKEN",
+            "signing_key",
+            "AWS_SECRET_ACCESS_KEY",
+        ] {
+            assert!(is_secret_name(name), "{name} should be secret");
+        }
+        for name in ["content-type", "idempotency-key", "CACHE_NAME", "keyspace"] {
+            assert!(!is_secret_name(name), "{name} should not be secret");
+        }
+    }
+
+    #[test]
+    fn test_format_http_request_redacts_credentials_and_pretty_prints_json() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("my-api-key"));
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+
+        let request = format_http_request(
+            &http::Method::PATCH,
+            "https://api.cache.example.com/capacity_pool/my-pool",
+            &headers,
+            Some(r#"{"provisioning":{"explicit":{"shard_count":3}}}"#),
+        );
+
+        assert_eq!(
+            "PATCH https://api.cache.example.com/capacity_pool/my-pool\n\
+             authorization: <redacted>\n\
+             content-type: application/json\n\
+             \n\
+             {\n  \"provisioning\": {\n    \"explicit\": {\n      \"shard_count\": 3\n    }\n  }\n}",
+            request
+        );
+    }
+
+    #[test]
+    fn test_format_http_request_with_plain_body() {
+        let request = format_http_request(
+            &http::Method::POST,
+            "https://api.cache.example.com/functions/my-cache/my-function",
+            &HeaderMap::new(),
+            Some("hello"),
+        );
+
+        assert_eq!(
+            "POST https://api.cache.example.com/functions/my-cache/my-function\n\nhello",
+            request
+        );
+    }
+
+    #[test]
+    fn test_format_sdk_request() {
+        let request = format_sdk_request(
+            "PutFunction",
+            &[
+                ("cache_name", "my-cache".to_string()),
+                ("env DB_PASSWORD", "hunter2".to_string()),
+            ],
+        );
+
+        assert_eq!(
+            "PutFunction\ncache_name: my-cache\nenv DB_PASSWORD: <redacted>",
+            request
+        );
+    }
+}
@@ -241,6 +241,7 @@ token=awesome-token
             Config {
                 cache: "awesome-cache".to_string(),
                 ttl: 90210,
+                ..Default::default()
             },
         )
         .join("\n");
@@ -351,6 +352,7 @@ ttl=600
         let config = Config {
             cache: "new-cache".to_string(),
             ttl: 90210,
+            ..Default::default()
         };
         let result = update_config_profile("default", &file_lines, config);
         assert!(result.is_ok());
@@ -388,6 +390,7 @@ ttl=600
         let config = Config {
             cache: "new-cache".to_string(),
             ttl: 90210,
+            ..Default::default()
         };
         let result = update_config_profile("default", &file_lines, config);
         assert!(result.is_ok());
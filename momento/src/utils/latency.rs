@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+/// Latency percentiles, in milliseconds.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LatencyPercentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub p999: f64,
+    pub max: f64,
+}
+
+impl LatencyPercentiles {
+    /// Summarizes latencies measured in microseconds; `None` when there are none.
+    pub fn from_micros(mut latencies: Vec<u64>) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort_unstable();
+        Some(Self {
+            p50: percentile(&latencies, 0.5),
+            p90: percentile(&latencies, 0.9),
+            p99: percentile(&latencies, 0.99),
+            p999: percentile(&latencies, 0.999),
+            max: percentile(&latencies, 1.0),
+        })
+    }
+}
+
+/// The latency at percentile `p` (0 to 1) of `sorted` microseconds, in milliseconds, by nearest rank.
+fn percentile(sorted: &[u64], p: f64) -> f64 {
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1] as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let latencies: Vec<u64> = (1..=1000).rev().map(|n| n * 1000).collect();
+        assert_eq!(
+            LatencyPercentiles::from_micros(latencies),
+            Some(LatencyPercentiles {
+                p50: 500.0,
+                p90: 900.0,
+                p99: 990.0,
+                p999: 999.0,
+                max: 1000.0,
+            })
+        );
+        assert_eq!(percentile(&[1500], 0.5), 1.5);
+        assert_eq!(LatencyPercentiles::from_micros(vec![]), None);
+    }
+}
@@ -1,5 +1,9 @@
 pub mod client;
+pub mod confirm;
 pub mod console;
+pub mod dry_run;
 pub mod file;
 pub mod ini_config;
+pub mod latency;
+pub mod trace;
 pub mod user;
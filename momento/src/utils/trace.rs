@@ -0,0 +1,245 @@
+use std::{
+    fs,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+
+use crate::{error::CliError, utils::console::console_info};
+
+static TRACER: OnceLock<Tracer> = OnceLock::new();
+
+struct Tracer {
+    started: Instant,
+    trace_file: Option<String>,
+    spans: Mutex<Vec<TraceSpan>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceKind {
+    Sdk,
+    Http,
+}
+
+impl TraceKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TraceKind::Sdk => "sdk",
+            TraceKind::Http => "http",
+        }
+    }
+}
+
+/// One request to Momento, as seen by `--trace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceSpan {
+    pub kind: TraceKind,
+    pub name: String,
+    /// Since tracing began.
+    pub start: Duration,
+    pub duration: Duration,
+    /// The HTTP status, or "ok" / the SDK error code.
+    pub outcome: String,
+    pub request_bytes: Option<usize>,
+    pub response_bytes: Option<usize>,
+}
+
+/// With `--trace`, every request to Momento is timed, and summarized when the command exits.
+/// A `trace_file` also gets the timings as Chrome trace events, for chrome://tracing or Perfetto.
+pub fn enable_trace(trace_file: Option<String>) {
+    let tracer = Tracer {
+        started: Instant::now(),
+        trace_file,
+        spans: Mutex::new(Vec::new()),
+    };
+    if TRACER.set(tracer).is_err() {
+        log::debug!("tracing was already enabled");
+    }
+}
+
+/// When tracing, the moment a request began; pass it to `record_span` once it finishes.
+pub fn start_span() -> Option<Instant> {
+    TRACER.get().map(|_| Instant::now())
+}
+
+pub fn record_span(
+    started: Option<Instant>,
+    kind: TraceKind,
+    name: String,
+    outcome: String,
+    request_bytes: Option<usize>,
+    response_bytes: Option<usize>,
+) {
+    let (Some(tracer), Some(started)) = (TRACER.get(), started) else {
+        return;
+    };
+    let span = TraceSpan {
+        kind,
+        name,
+        start: started.saturating_duration_since(tracer.started),
+        duration: started.elapsed(),
+        outcome,
+        request_bytes,
+        response_bytes,
+    };
+    match tracer.spans.lock() {
+        Ok(mut spans) => spans.push(span),
+        Err(poisoned) => poisoned.into_inner().push(span),
+    }
+}
+
+/// Prints the timings summary, and writes the trace file if one was asked for.
+/// Does nothing unless `--trace` is on; call it before the process exits.
+pub fn finish_trace() {
+    let Some(tracer) = TRACER.get() else {
+        return;
+    };
+    let spans = match tracer.spans.lock() {
+        Ok(spans) => spans.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    console_info!("{}", format_summary(&spans));
+    if let Some(trace_file) = &tracer.trace_file {
+        if let Err(e) = write_trace_file(trace_file, &spans) {
+            console_info!("{e}");
+        }
+    }
+}
+
+fn write_trace_file(trace_file: &str, spans: &[TraceSpan]) -> Result<(), CliError> {
+    let events = serde_json::to_string_pretty(&chrome_trace_events(spans))
+        .map_err(|e| CliError::new(format!("Could not serialize trace events: {e}")))?;
+    fs::write(trace_file, events)
+        .map_err(|e| CliError::new(format!("Could not write trace file {trace_file}: {e}")))?;
+    console_info!("Wrote trace events to {trace_file}");
+    Ok(())
+}
+
+fn format_bytes(bytes: Option<usize>) -> String {
+    bytes.map_or("-".to_string(), |bytes| bytes.to_string())
+}
+
+fn format_millis(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64() * 1000.0)
+}
+
+pub fn format_summary(spans: &[TraceSpan]) -> String {
+    if spans.is_empty() {
+        return "Trace: no requests were sent to Momento".to_string();
+    }
+    let total: Duration = spans.iter().map(|span| span.duration).sum();
+    let mut summary = format!(
+        "Trace: {} request(s), {} ms in total\n\n{:>10}  {:>10}  {:<4}  {:<24}  {:>8}  {:>8}  request",
+        spans.len(),
+        format_millis(total),
+        "start ms",
+        "took ms",
+        "kind",
+        "outcome",
+        "sent",
+        "received",
+    );
+    for span in spans {
+        summary.push_str(&format!(
+            "\n{:>10}  {:>10}  {:<4}  {:<24}  {:>8}  {:>8}  {}",
+            format_millis(span.start),
+            format_millis(span.duration),
+            span.kind.as_str(),
+            span.outcome,
+            format_bytes(span.request_bytes),
+            format_bytes(span.response_bytes),
+            span.name,
+        ));
+    }
+    summary
+}
+
+/// The spans in Chrome's trace event format, as complete ("X") events with microsecond times.
+pub fn chrome_trace_events(spans: &[TraceSpan]) -> serde_json::Value {
+    let events: Vec<serde_json::Value> = spans
+        .iter()
+        .map(|span| {
+            json!({
+                "name": span.name,
+                "cat": span.kind.as_str(),
+                "ph": "X",
+                "ts": span.start.as_micros() as u64,
+                "dur": span.duration.as_micros() as u64,
+                "pid": std::process::id(),
+                "tid": 1,
+                "args": {
+                    "outcome": span.outcome,
+                    "request_bytes": span.request_bytes,
+                    "response_bytes": span.response_bytes,
+                },
+            })
+        })
+        .collect();
+    json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans() -> Vec<TraceSpan> {
+        vec![
+            TraceSpan {
+                kind: TraceKind::Sdk,
+                name: "listing caches".to_string(),
+                start: Duration::from_micros(1_500),
+                duration: Duration::from_micros(42_200),
+                outcome: "ok".to_string(),
+                request_bytes: None,
+                response_bytes: None,
+            },
+            TraceSpan {
+                kind: TraceKind::Http,
+                name: "GET /ec/pools".to_string(),
+                start: Duration::from_millis(50),
+                duration: Duration::from_millis(120),
+                outcome: "200".to_string(),
+                request_bytes: Some(0),
+                response_bytes: Some(512),
+            },
+        ]
+    }
+
+    #[test]
+    fn summary_has_a_row_per_request() {
+        assert_eq!(
+            format_summary(&spans()),
+            "Trace: 2 request(s), 162.2 ms in total
+
+  start ms     took ms  kind  outcome                       sent  received  request
+       1.5        42.2  sdk   ok                               -         -  listing caches
+      50.0       120.0  http  200                              0       512  GET /ec/pools"
+        );
+    }
+
+    #[test]
+    fn summary_without_requests() {
+        assert_eq!(
+            format_summary(&[]),
+            "Trace: no requests were sent to Momento"
+        );
+    }
+
+    #[test]
+    fn chrome_trace_events_are_complete_events_in_microseconds() {
+        let events = chrome_trace_events(&spans());
+        let http = &events["traceEvents"][1];
+        assert_eq!(http["name"], "GET /ec/pools");
+        assert_eq!(http["cat"], "http");
+        assert_eq!(http["ph"], "X");
+        assert_eq!(http["ts"], 50_000);
+        assert_eq!(http["dur"], 120_000);
+        assert_eq!(http["args"]["outcome"], "200");
+        assert_eq!(http["args"]["response_bytes"], 512);
+        assert_eq!(
+            events["traceEvents"][0]["args"]["request_bytes"],
+            json!(null)
+        );
+    }
+}
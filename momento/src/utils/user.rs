@@ -2,7 +2,7 @@ use chrono::{Duration, TimeZone, Utc};
 use configparser::ini::Ini;
 
 use crate::{
-    config::{Config, Credentials},
+    config::{Config, Credentials, HttpSettings, ProtectedNames},
     error::CliError,
     utils::file::{get_config_file_path, get_credentials_file_path, read_ini_file},
 };
@@ -93,10 +93,16 @@ pub async fn get_config_for_profile(profile: &str) -> Result<Config, CliError> {
         )),
     };
 
+    let http = HttpSettings::from_profile_values(|key| configs.get(profile, key))?
+        .with_env_overrides(|name| std::env::var(name).ok())?;
+    let protected = ProtectedNames::from_profile_values(|key| configs.get(profile, key));
+
     Ok(Config {
         cache: cache_result,
         ttl: ttl_result
             .parse::<u64>()
             .map_err(|e| CliError::new(format!("could not parse a u64: {e:?}")))?,
+        http,
+        protected,
     })
 }